@@ -0,0 +1,573 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::constant::PROGRAM_START;
+use crate::instruction::Instruction;
+
+/// include 的最大嵌套深度，防止文件互相包含
+const MAX_INCLUDE_DEPTH: usize = 16;
+/// 常量之间的最大引用深度，防止常量引用自身
+const MAX_CONSTANT_DEPTH: usize = 32;
+
+/// 汇编错误，带有出错的文件名和行号
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// 源码中的位置
+#[derive(Debug, Clone)]
+struct Location {
+    file: String,
+    line: usize,
+}
+
+impl Location {
+    fn error(&self, message: String) -> AsmError {
+        AsmError { file: self.file.clone(), line: self.line, message }
+    }
+}
+
+/// 表达式中的一项
+#[derive(Debug, Clone)]
+enum Term {
+    Number(i64),
+    Symbol(String),
+}
+
+/// 由若干项相加减组成的表达式，每项带有符号（1 或 -1）
+type Expr = Vec<(i64, Term)>;
+
+/// 指令的操作数
+#[derive(Debug, Clone)]
+enum Operand {
+    V(usize),
+    I,
+    /// `[I]`
+    IndirectI,
+    Dt,
+    St,
+    K,
+    F,
+    Hf,
+    B,
+    R,
+    /// `LONG NNNN`，用于 XO-CHIP 的 `LD I, LONG NNNN`
+    Long(Expr),
+    Value(Expr),
+}
+
+#[derive(Debug, Clone)]
+enum Statement {
+    Instruction { mnemonic: String, operands: Vec<Operand> },
+    Bytes(Vec<Expr>),
+    Words(Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Symbol {
+    Label(u16),
+    Constant(Expr),
+}
+
+/// 汇编器
+///
+/// 第一遍读取所有源码（展开 include），计算每条语句的地址并记录标签和常量；
+/// 第二遍求值表达式并编码，因此标签可以在定义之前使用。
+struct Assembler {
+    // 下一条语句的地址
+    address: usize,
+    statements: Vec<(Location, Statement)>,
+    symbols: HashMap<String, (Symbol, Location)>,
+}
+
+/// 汇编源码，生成从 0x200 开始加载的 rom，等同于 `assemble_at(source, path, 0x200)`
+///
+/// `path` 用于错误信息中的文件名，`INCLUDE` 的路径相对于 `path` 所在的目录。
+///
+/// 语法与反汇编器的输出一致：
+///
+/// ```text
+/// SPEED = 2                ; 常量，也可以写作 SPEED EQU 2
+/// start:  LD V0, 0x00      ; 标签后可以跟指令
+///         ADD V0, SPEED
+///         LD I, sprite
+///         DRW V0, V1, 5
+///         JP start
+/// sprite: DB 0xF0, 0x90, 0x90, 0x90, 0xF0
+///         DW 0x1234, start
+///         INCLUDE "font.8s"
+/// ```
+pub fn assemble(source: &str, path: &Path) -> Result<Vec<u8>, AsmError> {
+    assemble_at(source, path, PROGRAM_START)
+}
+
+/// 汇编源码，生成从 `origin` 开始加载的 rom，标签的地址从 `origin` 开始计算
+pub fn assemble_at(source: &str, path: &Path, origin: u16) -> Result<Vec<u8>, AsmError> {
+    let mut assembler = Assembler { address: origin as usize, statements: Vec::new(), symbols: HashMap::new() };
+    assembler.source(source, path, 0)?;
+    assembler.encode()
+}
+
+impl Assembler {
+    fn source(&mut self, source: &str, path: &Path, depth: usize) -> Result<(), AsmError> {
+        for (i, line) in source.lines().enumerate() {
+            let location = Location { file: path.display().to_string(), line: i + 1 };
+            if let Some(include) = self.line(line, &location).map_err(|message| location.error(message))? {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(location.error("includes are nested too deeply".to_string()));
+                }
+                let include = path.parent().unwrap_or(Path::new("")).join(include);
+                let source = std::fs::read_to_string(&include)
+                    .map_err(|e| location.error(format!("cannot include {}: {}", include.display(), e)))?;
+                self.source(&source, &include, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 处理一行源码，遇到 `INCLUDE` 时返回要包含的文件
+    fn line(&mut self, line: &str, location: &Location) -> Result<Option<String>, String> {
+        let mut text = strip_comment(line).trim();
+        if let Some((name, rest)) = text.split_once(':') {
+            if is_identifier(name.trim()) {
+                self.define(name.trim(), Symbol::Label(self.address as u16), location)?;
+                text = rest.trim();
+            }
+        }
+        if text.is_empty() {
+            return Ok(None);
+        }
+
+        let (word, rest) = split_word(text);
+        let (second, value) = split_word(rest);
+        if second.eq_ignore_ascii_case("equ") || rest.starts_with('=') {
+            let value = if second.eq_ignore_ascii_case("equ") { value } else { &rest[1..] };
+            self.define(word, Symbol::Constant(parse_expr(value)?), location)?;
+            return Ok(None);
+        }
+
+        let mnemonic = word.to_ascii_uppercase();
+        let (statement, size) = match mnemonic.as_str() {
+            "INCLUDE" => return parse_string(rest).map(|path| Some(String::from_utf8_lossy(&path).into_owned())),
+            "DB" => {
+                let mut bytes = Vec::new();
+                for operand in split_operands(rest)? {
+                    if operand.starts_with('"') {
+                        let text = parse_string(operand)?;
+                        bytes.extend(text.into_iter().map(|c| vec![(1, Term::Number(c as i64))]));
+                    } else {
+                        bytes.push(parse_expr(operand)?);
+                    }
+                }
+                let size = bytes.len();
+                (Statement::Bytes(bytes), size)
+            }
+            "DW" => {
+                let words = split_operands(rest)?.into_iter().map(parse_expr).collect::<Result<Vec<_>, _>>()?;
+                let size = words.len() * 2;
+                (Statement::Words(words), size)
+            }
+            _ => {
+                let operands = split_operands(rest)?.into_iter().map(parse_operand).collect::<Result<Vec<_>, _>>()?;
+                let size = if operands.iter().any(|o| matches!(o, Operand::Long(_))) { 4 } else { 2 };
+                (Statement::Instruction { mnemonic, operands }, size)
+            }
+        };
+        self.address += size;
+        if self.address > 0x10000 {
+            return Err("program does not fit in 64 KiB".to_string());
+        }
+        self.statements.push((location.clone(), statement));
+        Ok(None)
+    }
+
+    fn define(&mut self, name: &str, symbol: Symbol, location: &Location) -> Result<(), String> {
+        if !is_identifier(name) {
+            return Err(format!("invalid name: {}", name));
+        }
+        if is_reserved(name) {
+            return Err(format!("{} is a reserved name", name));
+        }
+        if let Some((_, previous)) = self.symbols.get(name) {
+            return Err(format!("{} is already defined at {}:{}", name, previous.file, previous.line));
+        }
+        self.symbols.insert(name.to_string(), (symbol, location.clone()));
+        Ok(())
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, AsmError> {
+        let mut rom = Vec::new();
+        for (location, statement) in &self.statements {
+            let result = match statement {
+                Statement::Instruction { mnemonic, operands } => {
+                    self.instruction(mnemonic, operands).map(|instruction| rom.extend(instruction.encode()))
+                }
+                Statement::Bytes(bytes) => bytes.iter().try_for_each(|expr| {
+                    rom.push(self.ranged(expr, -0x80, 0xFF, "byte")? as u8);
+                    Ok(())
+                }),
+                Statement::Words(words) => words.iter().try_for_each(|expr| {
+                    rom.extend((self.ranged(expr, -0x8000, 0xFFFF, "word")? as u16).to_be_bytes());
+                    Ok(())
+                }),
+            };
+            result.map_err(|message| location.error(message))?;
+        }
+        Ok(rom)
+    }
+
+    fn instruction(&self, mnemonic: &str, operands: &[Operand]) -> Result<Instruction, String> {
+        use Operand::*;
+
+        let instruction = match (mnemonic, operands) {
+            ("CLS", []) => Instruction::Cls,
+            ("RET", []) => Instruction::Ret,
+            ("SCD", [Value(n)]) => Instruction::Scd(self.nibble(n)?),
+            ("SCR", []) => Instruction::Scr,
+            ("SCL", []) => Instruction::Scl,
+            ("EXIT", []) => Instruction::Exit,
+            ("LOW", []) => Instruction::Low,
+            ("HIGH", []) => Instruction::High,
+            ("JP", [Value(nnn)]) => Instruction::Jp(self.address(nnn)?),
+            ("JP", [V(0), Value(nnn)]) => Instruction::JpV0Addr(self.address(nnn)?),
+            ("CALL", [Value(nnn)]) => Instruction::Call(self.address(nnn)?),
+            ("SE", [V(x), V(y)]) => Instruction::SeVxVy { x: *x, y: *y },
+            ("SE", [V(x), Value(nn)]) => Instruction::SeVxByte { x: *x, nn: self.byte(nn)? },
+            ("SNE", [V(x), V(y)]) => Instruction::SneVxVy { x: *x, y: *y },
+            ("SNE", [V(x), Value(nn)]) => Instruction::SneVxByte { x: *x, nn: self.byte(nn)? },
+            ("SAVE", [V(x), V(y)]) => Instruction::SaveVxVy { x: *x, y: *y },
+            ("LOAD", [V(x), V(y)]) => Instruction::LoadVxVy { x: *x, y: *y },
+            ("LD", [V(x), V(y)]) => Instruction::LdVxVy { x: *x, y: *y },
+            ("LD", [V(x), Value(nn)]) => Instruction::LdVxByte { x: *x, nn: self.byte(nn)? },
+            ("LD", [I, Value(nnn)]) => Instruction::LdIAddr(self.address(nnn)?),
+            ("LD", [I, Long(nnnn)]) => Instruction::LdILong(self.ranged(nnnn, 0, 0xFFFF, "address")? as u16),
+            ("LD", [V(x), Dt]) => Instruction::LdVxDt { x: *x },
+            ("LD", [V(x), K]) => Instruction::LdVxK { x: *x },
+            ("LD", [Dt, V(x)]) => Instruction::LdDtVx { x: *x },
+            ("LD", [St, V(x)]) => Instruction::LdStVx { x: *x },
+            ("LD", [F, V(x)]) => Instruction::LdFVx { x: *x },
+            ("LD", [Hf, V(x)]) => Instruction::LdHfVx { x: *x },
+            ("LD", [B, V(x)]) => Instruction::LdBVx { x: *x },
+            ("LD", [IndirectI, V(x)]) => Instruction::LdIVx { x: *x },
+            ("LD", [V(x), IndirectI]) => Instruction::LdVxI { x: *x },
+            ("LD", [R, V(x)]) => Instruction::LdRVx { x: *x },
+            ("LD", [V(x), R]) => Instruction::LdVxR { x: *x },
+            ("ADD", [V(x), V(y)]) => Instruction::AddVxVy { x: *x, y: *y },
+            ("ADD", [V(x), Value(nn)]) => Instruction::AddVxByte { x: *x, nn: self.byte(nn)? },
+            ("ADD", [I, V(x)]) => Instruction::AddIVx { x: *x },
+            ("OR", [V(x), V(y)]) => Instruction::OrVxVy { x: *x, y: *y },
+            ("AND", [V(x), V(y)]) => Instruction::AndVxVy { x: *x, y: *y },
+            ("XOR", [V(x), V(y)]) => Instruction::XorVxVy { x: *x, y: *y },
+            ("SUB", [V(x), V(y)]) => Instruction::SubVxVy { x: *x, y: *y },
+            ("SUBN", [V(x), V(y)]) => Instruction::SubnVxVy { x: *x, y: *y },
+            // 省略 VY 时使用 VX，这样无论是否启用 shift_uses_vy 结果都相同
+            ("SHR", [V(x)]) => Instruction::ShrVxVy { x: *x, y: *x },
+            ("SHR", [V(x), V(y)]) => Instruction::ShrVxVy { x: *x, y: *y },
+            ("SHL", [V(x)]) => Instruction::ShlVxVy { x: *x, y: *x },
+            ("SHL", [V(x), V(y)]) => Instruction::ShlVxVy { x: *x, y: *y },
+            ("RND", [V(x), Value(nn)]) => Instruction::RndVxByte { x: *x, nn: self.byte(nn)? },
+            ("DRW", [V(x), V(y), Value(n)]) => Instruction::DrwVxVyNibble { x: *x, y: *y, n: self.nibble(n)? },
+            ("SKP", [V(x)]) => Instruction::SkpVx { x: *x },
+            ("SKNP", [V(x)]) => Instruction::SknpVx { x: *x },
+            ("PLANE", [Value(n)]) => Instruction::Plane(self.nibble(n)?),
+            ("AUDIO", []) => Instruction::Audio,
+            ("PITCH", [V(x)]) => Instruction::PitchVx { x: *x },
+            _ if MNEMONICS.contains(&mnemonic) => return Err(format!("invalid operands for {}", mnemonic)),
+            _ => return Err(format!("unknown instruction: {}", mnemonic)),
+        };
+        Ok(instruction)
+    }
+
+    fn evaluate(&self, expr: &Expr, depth: usize) -> Result<i64, String> {
+        expr.iter().try_fold(0i64, |sum, (sign, term)| {
+            let value = match term {
+                Term::Number(n) => *n,
+                Term::Symbol(name) => match self.symbols.get(name) {
+                    Some((Symbol::Label(address), _)) => *address as i64,
+                    Some((Symbol::Constant(_), _)) if depth >= MAX_CONSTANT_DEPTH => {
+                        return Err(format!("{} refers to itself", name));
+                    }
+                    Some((Symbol::Constant(expr), _)) => self.evaluate(expr, depth + 1)?,
+                    None => return Err(format!("undefined symbol: {}", name)),
+                },
+            };
+            sign.checked_mul(value).and_then(|value| sum.checked_add(value)).ok_or_else(|| "expression overflows".to_string())
+        })
+    }
+
+    fn ranged(&self, expr: &Expr, min: i64, max: i64, what: &str) -> Result<i64, String> {
+        let value = self.evaluate(expr, 0)?;
+        if value < min || value > max {
+            let hex = |n: i64| if n < 0 { format!("-{:#X}", -n) } else { format!("{:#X}", n) };
+            return Err(format!("{} {} is out of range ({}..={})", what, value, hex(min), hex(max)));
+        }
+        Ok(value)
+    }
+
+    fn address(&self, expr: &Expr) -> Result<u16, String> {
+        self.ranged(expr, 0, 0xFFF, "address").map(|value| value as u16)
+    }
+
+    /// 字节允许负数，按补码编码
+    fn byte(&self, expr: &Expr) -> Result<u8, String> {
+        self.ranged(expr, -0x80, 0xFF, "byte").map(|value| value as u8)
+    }
+
+    fn nibble(&self, expr: &Expr) -> Result<u8, String> {
+        self.ranged(expr, 0, 0xF, "nibble").map(|value| value as u8)
+    }
+}
+
+/// 所有助记符，用于区分未知指令和操作数错误
+const MNEMONICS: [&str; 30] = [
+    "CLS", "RET", "SCD", "SCR", "SCL", "EXIT", "LOW", "HIGH", "JP", "CALL", "SE", "SNE", "SAVE", "LOAD", "LD", "ADD",
+    "OR", "AND", "XOR", "SUB", "SUBN", "SHR", "SHL", "RND", "DRW", "SKP", "SKNP", "PLANE",
+    "AUDIO", "PITCH",
+];
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// 拆出第一个单词，返回单词和剩余部分
+fn split_word(text: &str) -> (&str, &str) {
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+/// 按逗号拆分操作数，忽略字符串中的逗号
+fn split_operands(text: &str) -> Result<Vec<&str>, String> {
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut operands = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                operands.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    operands.push(text[start..].trim());
+    if operands.iter().any(|operand| operand.is_empty()) {
+        return Err("missing operand".to_string());
+    }
+    Ok(operands)
+}
+
+fn parse_operand(text: &str) -> Result<Operand, String> {
+    let upper = text.to_ascii_uppercase();
+    let operand = match upper.as_str() {
+        "I" => Operand::I,
+        "[I]" => Operand::IndirectI,
+        "DT" => Operand::Dt,
+        "ST" => Operand::St,
+        "K" => Operand::K,
+        "F" => Operand::F,
+        "HF" => Operand::Hf,
+        "B" => Operand::B,
+        "R" => Operand::R,
+        _ => {
+            if let Some(x) = register(&upper) {
+                Operand::V(x)
+            } else if upper.starts_with("LONG ") {
+                Operand::Long(parse_expr(&text[5..])?)
+            } else {
+                Operand::Value(parse_expr(text)?)
+            }
+        }
+    };
+    Ok(operand)
+}
+
+fn register(text: &str) -> Option<usize> {
+    let digit = text.strip_prefix(['V', 'v'])?;
+    if digit.len() != 1 {
+        return None;
+    }
+    usize::from_str_radix(digit, 16).ok()
+}
+
+/// 解析 `a + b - c` 形式的表达式
+fn parse_expr(text: &str) -> Result<Expr, String> {
+    let mut expr = Vec::new();
+    let mut rest = text.trim();
+    let mut sign = 1;
+    if let Some(negated) = rest.strip_prefix('-') {
+        sign = -1;
+        rest = negated;
+    }
+    loop {
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        expr.push((sign, parse_term(rest[..end].trim())?));
+        if end == rest.len() {
+            return Ok(expr);
+        }
+        sign = if rest.as_bytes()[end] == b'+' { 1 } else { -1 };
+        rest = &rest[end + 1..];
+    }
+}
+
+/// 解析数字（`0x2A0`、`0b1010`、`42`）或符号名
+fn parse_term(text: &str) -> Result<Term, String> {
+    if text.is_empty() {
+        return Err("missing value".to_string());
+    }
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        let lower = text.to_ascii_lowercase();
+        let number = if let Some(hex) = lower.strip_prefix("0x") {
+            i64::from_str_radix(hex, 16)
+        } else if let Some(binary) = lower.strip_prefix("0b") {
+            i64::from_str_radix(binary, 2)
+        } else {
+            lower.parse()
+        };
+        return number.map(Term::Number).map_err(|_| format!("invalid number: {}", text));
+    }
+    if is_identifier(text) && !is_reserved(text) {
+        return Ok(Term::Symbol(text.to_string()));
+    }
+    Err(format!("invalid value: {}", text))
+}
+
+/// 解析带双引号的字符串
+fn parse_string(text: &str) -> Result<Vec<u8>, String> {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .filter(|text| !text.contains('"'))
+        .map(|text| text.as_bytes().to_vec())
+        .ok_or_else(|| format!("expected a quoted string: {}", text))
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// 寄存器名和特殊操作数不能用作标签或常量名
+fn is_reserved(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    register(&upper).is_some() || ["I", "DT", "ST", "K", "F", "HF", "B", "R", "LONG"].contains(&upper.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asm(source: &str) -> Result<Vec<u8>, AsmError> {
+        assemble(source, Path::new("test.8s"))
+    }
+
+    fn error(source: &str) -> String {
+        asm(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn labels_start_at_origin() {
+        let rom = assemble_at("start: JP start\nDW start", Path::new("test.8s"), 0x600).unwrap();
+        assert_eq!(rom, [0x16, 0x00, 0x06, 0x00]);
+    }
+
+    #[test]
+    fn assembles_instructions_and_labels() {
+        let source = "
+            ; 画一个数字然后停在原地
+            start:  CLS
+                    LD V3, 0x10
+                    add v1, 1
+                    LD I, sprite
+                    DRW V3, V1, 5
+            loop:   JP loop
+            sprite: DB 0xF0, 0x90
+        ";
+        assert_eq!(
+            asm(source).unwrap(),
+            [0x00, 0xE0, 0x63, 0x10, 0x71, 0x01, 0xA2, 0x0C, 0xD3, 0x15, 0x12, 0x0A, 0xF0, 0x90]
+        );
+    }
+
+    #[test]
+    fn disassembler_syntax_round_trips() {
+        let opcodes: [u16; 22] = [
+            0x00C4, 0x00FB, 0x00FF, 0x2ABC, 0x3A12, 0x5AB0, 0x5AB2, 0x5AB3, 0x8AB4, 0x8AB6, 0x8ABE, 0x9AB0, 0xB123,
+            0xC0FF, 0xD120, 0xE39E, 0xE4A1, 0xF201, 0xF002, 0xF53A, 0xF655, 0xF785,
+        ];
+        for opcode in opcodes {
+            let instruction = Instruction::decode(opcode).unwrap();
+            assert_eq!(asm(&instruction.to_string()).unwrap(), opcode.to_be_bytes(), "{}", instruction);
+        }
+        let long = Instruction::LdILong(0xE000);
+        assert_eq!(asm(&long.to_string()).unwrap(), long.encode());
+    }
+
+    #[test]
+    fn data_directives_and_constants() {
+        let source = "
+            COUNT = 3
+            BASE EQU here + COUNT - 1
+            here: DB COUNT, -1, \"AB;C\", 0b101 ; 注释
+                  DW BASE, 0x1234
+                  LD V0, COUNT
+        ";
+        assert_eq!(
+            asm(source).unwrap(),
+            [0x03, 0xFF, b'A', b'B', b';', b'C', 0x05, 0x02, 0x02, 0x12, 0x34, 0x60, 0x03]
+        );
+    }
+
+    #[test]
+    fn includes_are_relative_to_the_source() {
+        let dir = std::env::temp_dir().join(format!("chip8-asm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("sub.8s"), "sub: RET\n").unwrap();
+        let main = dir.join("main.8s");
+        let rom = assemble("CALL sub\nEXIT\nINCLUDE \"sub.8s\"", &main).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(rom, [0x22, 0x04, 0x00, 0xFD, 0x00, 0xEE]);
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        assert_eq!(error("CLS\nFOO V1"), "test.8s:2: unknown instruction: FOO");
+        assert_eq!(error("\n\nLD V0, V1, V2"), "test.8s:3: invalid operands for LD");
+        assert_eq!(error("JP nowhere"), "test.8s:1: undefined symbol: nowhere");
+        assert_eq!(error("LD V0, 0x100"), "test.8s:1: byte 256 is out of range (-0x80..=0xFF)");
+        assert_eq!(error("LD V0, -129"), "test.8s:1: byte -129 is out of range (-0x80..=0xFF)");
+        assert_eq!(error("JP 0x1000"), "test.8s:1: address 4096 is out of range (0x0..=0xFFF)");
+        assert_eq!(error("a: CLS\na: CLS"), "test.8s:2: a is already defined at test.8s:1");
+        assert_eq!(error("X = Y\nY = X\nLD V0, X"), "test.8s:3: X refers to itself");
+        assert_eq!(error("LD V0, 12z"), "test.8s:1: invalid number: 12z");
+        assert_eq!(error("I = 3"), "test.8s:1: I is a reserved name");
+        assert_eq!(error("BIG = 0x7FFFFFFFFFFFFFFF\nDW BIG + BIG"), "test.8s:2: expression overflows");
+        assert_eq!(error("DB 0 - 0x7FFFFFFFFFFFFFFF - 2"), "test.8s:1: expression overflows");
+        assert_eq!(error("INCLUDE \"missing.8s\"").split(':').take(3).collect::<Vec<_>>(), ["test.8s", "1", " cannot include missing.8s"]);
+    }
+}
@@ -0,0 +1,284 @@
+use std::f64::consts::TAU;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::chip8::Chip8;
+use crate::constant::{DEFAULT_SAMPLE_RATE, DEFAULT_TONE_FREQUENCY, DEFAULT_VOLUME, TIMER_FREQUENCY};
+use crate::frontend::AudioSink;
+
+/// 蜂鸣声的波形
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    /// 方波，接近原始硬件的蜂鸣声
+    Square,
+    /// 正弦波，听起来更柔和
+    Sine,
+}
+
+impl Waveform {
+    /// 按名称查找：`square`、`sine`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "square" => Some(Waveform::Square),
+            "sine" => Some(Waveform::Sine),
+            _ => None,
+        }
+    }
+}
+
+/// 蜂鸣声的参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tone {
+    /// 频率（Hz）
+    pub frequency: f32,
+    /// 音量，0.0 - 1.0
+    pub volume: f32,
+    /// 波形
+    pub waveform: Waveform,
+    /// 采样率（Hz）
+    pub sample_rate: u32,
+}
+
+/// 默认为 44.1kHz 采样的 440Hz 方波，音量 1/4
+impl Default for Tone {
+    fn default() -> Self {
+        Self {
+            frequency: DEFAULT_TONE_FREQUENCY,
+            volume: DEFAULT_VOLUME,
+            waveform: Waveform::Square,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+        }
+    }
+}
+
+/// 蜂鸣声发生器
+///
+/// 以帧（1/60 秒）为单位生成单声道 16 位 PCM 采样：声音定时器非零的帧输出蜂鸣声，否则输出静音。
+/// 采样率不能被 60 整除时，余下的采样数会累积到后续帧中，保证每秒的采样数准确。
+/// 连续发声时相位在帧之间保持连续，帧的边界不会产生杂音。
+pub struct ToneGenerator {
+    tone: Tone,
+    // 当前相位，0.0 - 1.0
+    phase: f64,
+    // 尚未分配到帧中的采样数（乘以 60）
+    sample_remainder: u32,
+    // 最近一帧的采样
+    buffer: Vec<i16>,
+}
+
+impl ToneGenerator {
+    /// 创建发生器，采样率至少为 1Hz
+    pub fn new(tone: Tone) -> Self {
+        let tone = Tone { sample_rate: tone.sample_rate.max(1), ..tone };
+        Self { tone, phase: 0.0, sample_remainder: 0, buffer: Vec::new() }
+    }
+
+    /// 蜂鸣声的参数
+    pub fn tone(&self) -> Tone {
+        self.tone
+    }
+
+    /// 生成一帧的采样，`active` 为 false 时输出静音
+    pub fn frame(&mut self, active: bool) -> &[i16] {
+        let total = self.tone.sample_rate + self.sample_remainder;
+        let count = (total / TIMER_FREQUENCY) as usize;
+        self.sample_remainder = total % TIMER_FREQUENCY;
+
+        self.buffer.clear();
+        if !active {
+            // 下一次发声从零相位开始
+            self.phase = 0.0;
+            self.buffer.resize(count, 0);
+            return &self.buffer;
+        }
+        let amplitude = f64::from(self.tone.volume.clamp(0.0, 1.0)) * f64::from(i16::MAX);
+        let step = f64::from(self.tone.frequency) / f64::from(self.tone.sample_rate);
+        for _ in 0..count {
+            let value = match self.tone.waveform {
+                Waveform::Square => {
+                    if self.phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Waveform::Sine => (self.phase * TAU).sin(),
+            };
+            self.buffer.push((value * amplitude).round() as i16);
+            self.phase = (self.phase + step).fract();
+        }
+        &self.buffer
+    }
+
+    /// 最近一帧的采样
+    pub fn samples(&self) -> &[i16] {
+        &self.buffer
+    }
+}
+
+/// 16 位单声道 PCM 格式的 WAV 文件写入器
+///
+/// 文件头中的长度在 `finish` 时补写，因此输出需要支持 `Seek`。
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    // 已写入的采样数据的字节数
+    data_len: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// 写入文件头
+    pub fn new(mut writer: W, sample_rate: u32) -> io::Result<Self> {
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&36u32.to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // PCM，单声道
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        // 每秒字节数、每个采样的字节数、位深
+        writer.write_all(&(sample_rate * 2).to_le_bytes())?;
+        writer.write_all(&2u16.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self { writer, data_len: 0 })
+    }
+
+    /// 追加采样
+    pub fn write_samples(&mut self, samples: &[i16]) -> io::Result<()> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        self.writer.write_all(&bytes)?;
+        self.data_len = self.data_len.saturating_add(bytes.len() as u32);
+        Ok(())
+    }
+
+    /// 补写文件头中的长度，返回底层的输出
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&self.data_len.saturating_add(36).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&self.data_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// 创建 WAV 文件
+    pub fn create(path: impl AsRef<Path>, sample_rate: u32) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), sample_rate)
+    }
+}
+
+/// 将每帧的蜂鸣声录制到 WAV 文件，不需要声卡
+pub struct WavRecorder<W: Write + Seek> {
+    generator: ToneGenerator,
+    wav: WavWriter<W>,
+}
+
+impl<W: Write + Seek> WavRecorder<W> {
+    /// 录制到 `writer`
+    pub fn new(writer: W, tone: Tone) -> io::Result<Self> {
+        let generator = ToneGenerator::new(tone);
+        let wav = WavWriter::new(writer, generator.tone().sample_rate)?;
+        Ok(Self { generator, wav })
+    }
+
+    /// 结束录制，返回底层的输出
+    pub fn finish(self) -> io::Result<W> {
+        self.wav.finish()
+    }
+}
+
+impl WavRecorder<BufWriter<File>> {
+    /// 录制到文件
+    pub fn create(path: impl AsRef<Path>, tone: Tone) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), tone)
+    }
+}
+
+impl<W: Write + Seek> AudioSink for WavRecorder<W> {
+    fn update(&mut self, chip8: &Chip8) -> io::Result<()> {
+        let samples = self.generator.frame(chip8.is_sound_active());
+        self.wav.write_samples(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn tone(frequency: f32, waveform: Waveform, sample_rate: u32) -> Tone {
+        Tone { frequency, volume: 1.0, waveform, sample_rate }
+    }
+
+    #[test]
+    fn frames_carry_over_sample_remainder() {
+        let mut generator = ToneGenerator::new(tone(440.0, Waveform::Square, 22050));
+        let counts: Vec<usize> = (0..60).map(|_| generator.frame(false).len()).collect();
+        assert_eq!(counts[..2], [367, 368]);
+        assert_eq!(counts.iter().sum::<usize>(), 22050);
+        assert!(generator.samples().iter().all(|&sample| sample == 0));
+    }
+
+    #[test]
+    fn square_wave_alternates_every_half_period() {
+        // 每个周期 4 个采样
+        let mut generator = ToneGenerator::new(Tone { volume: 0.5, ..tone(11025.0, Waveform::Square, 44100) });
+        let samples = generator.frame(true);
+        assert_eq!(samples.len(), 735);
+        assert_eq!(samples[..6], [16384, 16384, -16384, -16384, 16384, 16384]);
+    }
+
+    #[test]
+    fn sine_wave_is_continuous_across_frames() {
+        // 每个周期 8 个采样，每帧 735 个采样，第二帧从相位 735 % 8 = 7 开始
+        let mut generator = ToneGenerator::new(tone(5512.5, Waveform::Sine, 44100));
+        let first = generator.frame(true).to_vec();
+        assert_eq!(first[..3], [0, 23170, 32767]);
+        let second = generator.frame(true);
+        assert_eq!(second[0], -23170);
+        assert_eq!(second[1], 0);
+
+        // 静音之后重新从零相位开始
+        generator.frame(false);
+        assert_eq!(generator.frame(true)[..2], first[..2]);
+    }
+
+    #[test]
+    fn wav_header_records_lengths() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 8000).unwrap();
+        wav.write_samples(&[1, -1, 0x1234]).unwrap();
+        let data = wav.finish().unwrap().into_inner();
+        assert_eq!(data.len(), 44 + 6);
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(data[4..8], 42u32.to_le_bytes());
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        assert_eq!(data[24..28], 8000u32.to_le_bytes());
+        assert_eq!(data[28..32], 16000u32.to_le_bytes());
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(data[40..44], 6u32.to_le_bytes());
+        assert_eq!(data[44..], [0x01, 0x00, 0xFF, 0xFF, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn recorder_follows_sound_timer() {
+        let mut recorder = WavRecorder::new(Cursor::new(Vec::new()), tone(600.0, Waveform::Square, 600)).unwrap();
+        let mut chip8 = Chip8::new();
+        chip8.set_sound_timer(1);
+        recorder.update(&chip8).unwrap();
+        chip8.tick_timers();
+        recorder.update(&chip8).unwrap();
+        let data = recorder.finish().unwrap().into_inner();
+        // 每帧 10 个采样，第一帧发声，第二帧静音
+        let samples: Vec<i16> = data[44..].chunks(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+        assert_eq!(samples.len(), 20);
+        assert!(samples[..10].iter().all(|&sample| sample == i16::MAX));
+        assert!(samples[10..].iter().all(|&sample| sample == 0));
+    }
+}
@@ -1,5 +1,13 @@
 use std::num::Wrapping;
-use crate::constant::{CHIP8_MEMORY, FONT_SET, INSTRUCTION_LENGTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::constant::{
+    AUDIO_PATTERN_LENGTH, DEFAULT_PITCH, FONT_ADDRESS, FONT_SET, FONT_SIZE, HIRES_SCREEN_HEIGHT, HIRES_SCREEN_WIDTH,
+    INSTRUCTION_LENGTH, PROGRAM_START, SCREEN_HEIGHT, SCREEN_WIDTH,
+};
+use crate::error::Chip8Error;
+use crate::font::Font;
+use crate::instruction::Instruction;
+use crate::quirks::Quirks;
+use crate::state::{StateReader, StateWriter};
 
 /// Chip8 解释器
 ///
@@ -11,12 +19,25 @@ use crate::constant::{CHIP8_MEMORY, FONT_SET, INSTRUCTION_LENGTH, SCREEN_HEIGHT,
 ///
 /// 大多数Chip-8程序从位置 0x200（512）开始。
 ///
-/// 例外：但有些程序从 0x600（1536）开始。以 0x600开始的程序是为 ETI 660计算机准备的，我们不考虑这种情况。
+/// 例外：但有些程序从 0x600（1536）开始。以 0x600开始的程序是为 ETI 660计算机准备的，
+/// 还有一些 CHIP-8 HIRES 程序从 0x200 加载但从 0x2C0 或 0x260 开始执行，这些程序使用 `load_rom_at` 加载。
 pub struct Chip8 {
-    // 屏幕
-    screen: [[u8; SCREEN_WIDTH]; SCREEN_HEIGHT],
-    // 内存
-    memory: [u8; CHIP8_MEMORY],
+    // 屏幕，按行存储 screen_width * screen_height 个像素
+    // 每个像素的 bit 0 为第一平面，bit 1 为第二平面（XO-CHIP）
+    screen: Vec<u8>,
+    // 绘制、清屏和滚动所作用的平面，默认只有第一平面
+    plane_mask: u8,
+    // 当前分辨率，低分辨率 64 x 32，SUPER-CHIP 高分辨率 128 x 64
+    screen_width: usize,
+    screen_height: usize,
+    // 屏幕自上次绘制后是否发生了变化
+    display_dirty: bool,
+    // 自上次绘制后是否经过了垂直同步，用于 `Quirks::display_wait`
+    vertical_blank: bool,
+    // 内存，4KB，XO-CHIP 模式下为 64KB
+    memory: Vec<u8>,
+    // 小字体在内存中的起始地址，大字体紧随其后
+    font_address: usize,
     // 一个长度为 16 的数组，表示虚拟机的通用寄存器。
     data_register: [u8; 16],
     //  一个 16 位的寄存器，可以用来存储内存地址 I
@@ -28,8 +49,12 @@ pub struct Chip8 {
     sound_timer: u8,
     // 一个长度为 16 的布尔数组，表示虚拟机的键盘
     keyboard: [bool; 16],
+    // FX0A 正在等待按键
     keyboard_waiting: bool,
+    // FX0A 等待结束后存放按键值的寄存器 X
     keyboard_register: usize,
+    // FX0A 开始等待之后按下的按键，只有松开这些按键才结束等待
+    keys_pressed_while_waiting: [bool; 16],
 
     // 一个长度为 16 的数组，用于实现函数调用和返回
     stack: [u16; 16],
@@ -37,185 +62,2237 @@ pub struct Chip8 {
     stack_pointer: usize,
     // 一个 16 位的寄存器，用于存储当前执行的指令地址
     program_counter: u16,
+    // SUPER-CHIP 的 RPL 用户标志，FX75 / FX85 使用
+    rpl_flags: [u8; 16],
+    // 是否执行了 00FD 退出
+    exited: bool,
+    // XO-CHIP 音频模式缓冲区，每一位是一个 1 bit 采样
+    audio_pattern: [u8; AUDIO_PATTERN_LENGTH],
+    // XO-CHIP 音高寄存器，决定音频模式的播放速率
+    pitch: u8,
+    // 随机数生成器状态（xorshift）
+    random_state: u32,
+    // 兼容性配置
+    quirks: Quirks,
+}
+
+/// 单步执行的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepInfo {
+    /// 指令所在地址
+    pub pc: u16,
+    /// 原始操作码
+    pub opcode: u16,
+    /// 解码后的指令
+    pub instruction: Instruction,
 }
 
 /// 指令
 pub trait Instructions {
-    /// 清屏
+    /// 清屏，只清除当前选中的平面
     ///
     /// 00E0
-    fn cls(&mut self);
+    fn cls(&mut self) -> Result<(), Chip8Error>;
 
     /// 从子程序返回
     ///
     /// 00EE
-    fn ret(&mut self);
+    fn ret(&mut self) -> Result<(), Chip8Error>;
+
+    /// 屏幕向下滚动 N 个像素
+    ///
+    /// 00CN（SUPER-CHIP）
+    fn scd(&mut self, n: u8) -> Result<(), Chip8Error>;
+
+    /// 屏幕向右滚动 4 个像素
+    ///
+    /// 00FB（SUPER-CHIP）
+    fn scr(&mut self) -> Result<(), Chip8Error>;
+
+    /// 屏幕向左滚动 4 个像素
+    ///
+    /// 00FC（SUPER-CHIP）
+    fn scl(&mut self) -> Result<(), Chip8Error>;
+
+    /// 退出解释器
+    ///
+    /// 00FD（SUPER-CHIP）
+    fn exit(&mut self) -> Result<(), Chip8Error>;
+
+    /// 切换到 64 x 32 低分辨率模式并清屏
+    ///
+    /// 00FE（SUPER-CHIP）
+    fn low(&mut self) -> Result<(), Chip8Error>;
+
+    /// 切换到 128 x 64 高分辨率模式并清屏
+    ///
+    /// 00FF（SUPER-CHIP）
+    fn high(&mut self) -> Result<(), Chip8Error>;
 
     /// 跳转地址到 NNN
     ///
     /// 1NNN
-    fn jp(&mut self);
+    fn jp(&mut self, nnn: u16) -> Result<(), Chip8Error>;
 
     /// 从地址 NNN 开始执行子程序
     ///
     /// 2NNN
-    fn call(&mut self);
+    fn call(&mut self, nnn: u16) -> Result<(), Chip8Error>;
 
     /// 如果寄存器 VX 的值等于 NN，则跳过下面的指令
     ///
     /// 3XNN
-    fn se_vx_byte(&mut self);
+    fn se_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error>;
 
     /// 如果寄存器 VX 的值不等于 NN，则跳过下面的指令
     ///
     /// 4XNN
-    fn sne_vx_byte(&mut self);
+    fn sne_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error>;
 
     /// 如果寄存器 VX 的值等于寄存器 VY 的值，则跳过下面的指令
     ///
     /// 5XY0
-    fn se_vx_vy(&mut self);
+    fn se_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// 将 VX 到 VY 存储到从 I 开始的内存中，I 保持不变
+    ///
+    /// 5XY2（XO-CHIP）
+    fn save_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// 从 I 开始的内存中读取数据到 VX 到 VY，I 保持不变
+    ///
+    /// 5XY3（XO-CHIP）
+    fn load_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
 
     /// 在寄存器 VX 中存储编号 NN
     ///
     /// 6XNN
-    fn ld_vx_byte(&mut self);
+    fn ld_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error>;
 
     /// VX = VX + NN
     ///
     /// 7XNN
-    fn add_vx_byte(&mut self);
+    fn add_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error>;
+
+    /// VX = VY
+    ///
+    /// 8XY0
+    fn ld_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX | VY，`Quirks::logic_resets_vf` 时 VF = 0
+    ///
+    /// 8XY1
+    fn or_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX & VY，`Quirks::logic_resets_vf` 时 VF = 0
+    ///
+    /// 8XY2
+    fn and_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX ^ VY，`Quirks::logic_resets_vf` 时 VF = 0
+    ///
+    /// 8XY3
+    fn xor_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX + VY，产生进位时 VF = 1，否则 VF = 0
+    ///
+    /// 8XY4
+    fn add_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX - VY，没有借位时 VF = 1，否则 VF = 0
+    ///
+    /// 8XY5
+    fn sub_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX >> 1，VF 为移出的最低位（`Quirks::shift_uses_vy` 时 VX = VY >> 1）
+    ///
+    /// 8XY6
+    fn shr_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VY - VX，没有借位时 VF = 1，否则 VF = 0
+    ///
+    /// 8XY7
+    fn subn_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// VX = VX << 1，VF 为移出的最高位（`Quirks::shift_uses_vy` 时 VX = VY << 1）
+    ///
+    /// 8XYE
+    fn shl_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// 如果寄存器 VX 的值不等于寄存器 VY 的值，则跳过下面的指令
+    ///
+    /// 9XY0
+    fn sne_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error>;
+
+    /// 在地址寄存器 I 中存储地址 NNN
+    ///
+    /// ANNN
+    fn ld_i_addr(&mut self, nnn: u16) -> Result<(), Chip8Error>;
+
+    /// 跳转到地址 NNN + V0（`Quirks::jump_uses_vx` 时按 BXNN 跳转到 XNN + VX）
+    ///
+    /// BNNN
+    fn jp_v0_addr(&mut self, nnn: u16) -> Result<(), Chip8Error>;
+
+    /// VX = 随机数 & NN
+    ///
+    /// CXNN
+    fn rnd_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error>;
+
+    /// 在坐标 (VX, VY) 绘制从 I 开始的 N 字节精灵，发生碰撞时 VF = 1，否则 VF = 0
+    ///
+    /// N 为 0 时绘制从 I 开始 32 字节的 16 x 16 精灵（SUPER-CHIP）
+    ///
+    /// DXYN
+    fn drw_vx_vy_nibble(&mut self, x: usize, y: usize, n: u8) -> Result<(), Chip8Error>;
+
+    /// 如果 VX 对应的按键被按下，则跳过下面的指令
+    ///
+    /// EX9E
+    fn skp_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 如果 VX 对应的按键没有被按下，则跳过下面的指令
+    ///
+    /// EXA1
+    fn sknp_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 在地址寄存器 I 中存储紧随其后的 16 位地址 NNNN
+    ///
+    /// F000 NNNN（XO-CHIP）
+    fn ld_i_long(&mut self, nnnn: u16) -> Result<(), Chip8Error>;
+
+    /// 选择绘制平面，N 为平面掩码 0 - 3
+    ///
+    /// FN01（XO-CHIP）
+    fn plane(&mut self, n: u8) -> Result<(), Chip8Error>;
+
+    /// 将从 I 开始的 16 字节加载到音频模式缓冲区
+    ///
+    /// F002（XO-CHIP）
+    fn audio(&mut self) -> Result<(), Chip8Error>;
+
+    /// VX = 延迟定时器的值
+    ///
+    /// FX07
+    fn ld_vx_dt(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 等待按键，并将按键值存储到 VX
+    ///
+    /// FX0A
+    fn ld_vx_k(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 延迟定时器 = VX
+    ///
+    /// FX15
+    fn ld_dt_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 声音定时器 = VX
+    ///
+    /// FX18
+    fn ld_st_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// I = I + VX
+    ///
+    /// FX1E
+    fn add_i_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// I = VX 对应的字符精灵地址
+    ///
+    /// FX29
+    fn ld_f_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// I = VX 对应的大字符精灵地址
+    ///
+    /// FX30（SUPER-CHIP）
+    fn ld_hf_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 将 VX 的 BCD 码存储到 I、I + 1、I + 2
+    ///
+    /// FX33
+    fn ld_b_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 音高寄存器 = VX
+    ///
+    /// FX3A（XO-CHIP）
+    fn pitch_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 将 V0 到 VX 存储到从 I 开始的内存中（`Quirks::load_store_increments_i` 时 I 增加 X + 1）
+    ///
+    /// FX55
+    fn ld_i_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 从 I 开始的内存中读取数据到 V0 到 VX（`Quirks::load_store_increments_i` 时 I 增加 X + 1）
+    ///
+    /// FX65
+    fn ld_vx_i(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 将 V0 到 VX 存储到 RPL 用户标志中（X <= 7）
+    ///
+    /// FX75（SUPER-CHIP）
+    fn ld_r_vx(&mut self, x: usize) -> Result<(), Chip8Error>;
+
+    /// 从 RPL 用户标志中读取 V0 到 VX（X <= 7）
+    ///
+    /// FX85（SUPER-CHIP）
+    fn ld_vx_r(&mut self, x: usize) -> Result<(), Chip8Error>;
 }
 
 impl Chip8 {
     /// 创建Chip8
     pub fn new() -> Self {
-        // 将字体放置在内存的前 80 个字节
-        let mut memory = [0u8; CHIP8_MEMORY];
-        for i in 0..FONT_SET.len() {
-            memory[i] = FONT_SET[i];
-        }
+        Self::new_with(Quirks::default())
+    }
+
+    /// 使用指定的兼容性配置创建Chip8
+    pub fn new_with(quirks: Quirks) -> Self {
+        // 将字体放置在内存的前 80 个字节，大字体紧随其后
+        let mut memory = vec![0u8; quirks.memory_size()];
+        memory[FONT_ADDRESS..FONT_ADDRESS + FONT_SIZE].copy_from_slice(&Font::default().to_bytes());
 
-        return Self {
-            screen: [[0; SCREEN_WIDTH]; SCREEN_HEIGHT],
+        Self {
+            screen: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            screen_width: SCREEN_WIDTH,
+            screen_height: SCREEN_HEIGHT,
+            plane_mask: 1,
+            display_dirty: true,
+            vertical_blank: true,
             memory,
+            font_address: FONT_ADDRESS,
             data_register: [0; 16],
-            program_counter: 0x200,
+            program_counter: PROGRAM_START,
             delay_timer: 0,
             sound_timer: 0,
             keyboard: [false; 16],
             keyboard_waiting: false,
             keyboard_register: 0,
+            keys_pressed_while_waiting: [false; 16],
             address_register: 0,
             stack: [0; 16],
             stack_pointer: 0,
-        };
+            rpl_flags: [0; 16],
+            exited: false,
+            audio_pattern: [0; AUDIO_PATTERN_LENGTH],
+            pitch: DEFAULT_PITCH,
+            random_state: 0x2545_F491,
+            quirks,
+        }
+    }
+
+    /// 更换字体并放置到 `address`，FX29 / FX30 返回新位置中的字符地址
+    ///
+    /// 原位置的字体会被清除，因此应当在加载 rom 之前调用。
+    /// 字体超出内存时返回 `Chip8Error::FontOutOfBounds`，此时内存不会被修改。
+    pub fn set_font(&mut self, font: &Font, address: u16) -> Result<(), Chip8Error> {
+        let start = address as usize;
+        if start + FONT_SIZE > self.memory.len() {
+            return Err(Chip8Error::FontOutOfBounds(address));
+        }
+        // set_quirks 缩小内存后原位置可能已不在内存中
+        if let Some(old) = self.memory.get_mut(self.font_address..self.font_address + FONT_SIZE) {
+            old.fill(0);
+        }
+        self.memory[start..start + FONT_SIZE].copy_from_slice(&font.to_bytes());
+        self.font_address = start;
+        Ok(())
+    }
+
+    /// 小字体在内存中的起始地址，大字体紧随其后
+    pub fn font_address(&self) -> u16 {
+        self.font_address as u16
+    }
+
+    /// 读取游戏 rom，从 0x200 加载并开始执行
+    ///
+    /// rom 超出 0x200 之后的可用内存时返回 `Chip8Error::RomTooLarge`，此时内存不会被修改。
+    pub fn load_rom(&mut self, rom_data: &[u8]) -> Result<(), Chip8Error> {
+        self.load_rom_at(rom_data, PROGRAM_START, PROGRAM_START)
+    }
+
+    /// 将 rom 加载到 `load_address`，并从 `entry_point` 开始执行
+    ///
+    /// rom 超出 `load_address` 之后的可用内存时返回 `Chip8Error::RomTooLarge`，
+    /// 入口地址不在内存中时返回 `Chip8Error::PcOutOfBounds`，出错时内存和 PC 都不会被修改。
+    pub fn load_rom_at(&mut self, rom_data: &[u8], load_address: u16, entry_point: u16) -> Result<(), Chip8Error> {
+        let start = load_address as usize;
+        let max = self.memory.len().saturating_sub(start);
+        if rom_data.len() > max {
+            return Err(Chip8Error::RomTooLarge { size: rom_data.len(), max });
+        }
+        if entry_point as usize >= self.memory.len() {
+            return Err(Chip8Error::PcOutOfBounds(entry_point));
+        }
+        self.memory[start..start + rom_data.len()].copy_from_slice(rom_data);
+        self.program_counter = entry_point;
+        Ok(())
+    }
+
+    /// 读取通用寄存器 VX
+    pub fn v(&self, x: usize) -> u8 {
+        self.data_register[x & 0x0F]
+    }
+
+    /// 写入通用寄存器 VX
+    pub fn set_v(&mut self, x: usize, value: u8) {
+        self.data_register[x & 0x0F] = value;
+    }
+
+    /// 只读的通用寄存器 V0 - VF
+    pub fn registers(&self) -> &[u8; 16] {
+        &self.data_register
+    }
+
+    /// 地址寄存器 I
+    pub fn address_register(&self) -> u16 {
+        self.address_register
+    }
+
+    /// 程序计数器，即下一条要执行的指令的地址
+    pub fn program_counter(&self) -> u16 {
+        self.program_counter
+    }
+
+    /// 调用栈中的返回地址，最早压入的在前
+    pub fn stack(&self) -> &[u16] {
+        &self.stack[..self.stack_pointer]
+    }
+
+    /// 延迟计时器
+    pub fn delay_timer(&self) -> u8 {
+        self.delay_timer
+    }
+
+    /// 声音计时器
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    /// 只读的内存
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// 写入地址寄存器 I
+    pub fn set_address_register(&mut self, value: u16) {
+        self.address_register = value;
+    }
+
+    /// 设置下一条要执行的指令的地址
+    pub fn set_program_counter(&mut self, value: u16) {
+        self.program_counter = value;
+    }
+
+    /// 替换调用栈，超过栈深度时返回 `Chip8Error::StackOverflow`，此时栈不会被修改
+    pub fn set_stack(&mut self, stack: &[u16]) -> Result<(), Chip8Error> {
+        if stack.len() > self.stack.len() {
+            return Err(Chip8Error::StackOverflow);
+        }
+        self.stack[..stack.len()].copy_from_slice(stack);
+        self.stack_pointer = stack.len();
+        Ok(())
     }
 
-    /// 读取游戏 rom
-    pub fn load_rom(&mut self, rom_data: &[u8]) {
-        for (i, &byte) in rom_data.iter().enumerate() {
-            self.memory[0x200 + i] = byte;
+    /// 写入延迟计时器
+    pub fn set_delay_timer(&mut self, value: u8) {
+        self.delay_timer = value;
+    }
+
+    /// 写入声音计时器
+    pub fn set_sound_timer(&mut self, value: u8) {
+        self.sound_timer = value;
+    }
+
+    /// 可写的内存，长度为 `memory_size()`，包括字体所在的前 512 字节
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    /// 只读的帧缓冲，按行存储，每个像素的 bit 0 / bit 1 分别为第一 / 第二平面，取值 0 - 3
+    ///
+    /// 长度为 `screen_width() * screen_height()`，切换分辨率后会改变。
+    pub fn screen(&self) -> &[u8] {
+        &self.screen
+    }
+
+    /// 当前屏幕宽度（像素）
+    pub fn screen_width(&self) -> usize {
+        self.screen_width
+    }
+
+    /// 当前屏幕高度（像素）
+    pub fn screen_height(&self) -> usize {
+        self.screen_height
+    }
+
+    /// 是否处于 SUPER-CHIP 高分辨率模式
+    pub fn is_hires(&self) -> bool {
+        self.screen_width == HIRES_SCREEN_WIDTH
+    }
+
+    /// 像素 (x, y) 是否点亮，超出屏幕的坐标返回 false
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        x < self.screen_width && y < self.screen_height && self.screen[y * self.screen_width + x] != 0
+    }
+
+    /// 设置像素 (x, y) 的平面值（0 - 3），超出屏幕的坐标被忽略
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: u8) {
+        if x < self.screen_width && y < self.screen_height {
+            self.screen[y * self.screen_width + x] = value & 0b11;
+            self.display_dirty = true;
+        }
+    }
+
+    /// 程序是否已通过 00FD 退出
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    /// 切换分辨率并清屏
+    fn set_resolution(&mut self, width: usize, height: usize) {
+        self.screen_width = width;
+        self.screen_height = height;
+        self.screen = vec![0; width * height];
+        self.display_dirty = true;
+    }
+
+    /// 屏幕自上次 `take_display_dirty` 之后是否发生了变化
+    pub fn is_display_dirty(&self) -> bool {
+        self.display_dirty
+    }
+
+    /// 读取并清除屏幕变化标志，渲染器只需在返回 true 时重绘
+    pub fn take_display_dirty(&mut self) -> bool {
+        std::mem::replace(&mut self.display_dirty, false)
+    }
+
+    /// 当前的兼容性配置
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    /// 修改兼容性配置，从下一条指令开始生效
+    ///
+    /// 切换 XO-CHIP 模式会调整内存大小，从 64KB 切换到 4KB 时超出部分会被丢弃：
+    /// 放不下的字体移回默认位置，PC、I 和栈中的返回地址按新的内存大小回绕。
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        let size = quirks.memory_size();
+        if self.font_address + FONT_SIZE > size {
+            let old = self.font_address..self.font_address + FONT_SIZE;
+            let font = self.memory[old.clone()].to_vec();
+            self.memory[old].fill(0);
+            self.memory[FONT_ADDRESS..FONT_ADDRESS + FONT_SIZE].copy_from_slice(&font);
+            self.font_address = FONT_ADDRESS;
+        }
+        self.memory.resize(size, 0);
+        let wrap = |address: u16| (address as usize % size) as u16;
+        self.program_counter = wrap(self.program_counter);
+        self.address_register = wrap(self.address_register);
+        for address in &mut self.stack[..self.stack_pointer] {
+            *address = wrap(*address);
+        }
+        self.quirks = quirks;
+    }
+
+    /// 按下按键 0x0 - 0xF，超出范围的按键被忽略
+    pub fn press_key(&mut self, key: usize) {
+        if key < self.keyboard.len() {
+            if self.keyboard_waiting && !self.keyboard[key] {
+                self.keys_pressed_while_waiting[key] = true;
+            }
+            self.keyboard[key] = true;
+        }
+    }
+
+    /// 松开按键 0x0 - 0xF，超出范围的按键被忽略
+    ///
+    /// 如果 FX0A 正在等待输入，松开一个在等待开始后按下的按键会把按键值写入 VX 并结束等待，
+    /// 松开等待之前就已按住的按键不会结束等待。
+    pub fn release_key(&mut self, key: usize) {
+        if key >= self.keyboard.len() || !self.keyboard[key] {
+            return;
+        }
+        self.keyboard[key] = false;
+        if self.keyboard_waiting && self.keys_pressed_while_waiting[key] {
+            self.keyboard_waiting = false;
+            self.set_v(self.keyboard_register, key as u8);
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        }
+    }
+
+    /// 一次性设置全部 16 个按键的状态
+    pub fn set_keys(&mut self, keys: [bool; 16]) {
+        for (key, &pressed) in keys.iter().enumerate() {
+            if pressed {
+                self.press_key(key);
+            } else {
+                self.release_key(key);
+            }
+        }
+    }
+
+    /// 按键当前是否被按下
+    pub fn is_key_pressed(&self, key: usize) -> bool {
+        key < self.keyboard.len() && self.keyboard[key]
+    }
+
+    /// CPU 是否因 FX0A 阻塞等待按键
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.keyboard_waiting
+    }
+
+    /// 将延迟定时器和声音定时器各减一，应以 60Hz 的频率调用
+    pub fn tick_timers(&mut self) {
+        self.vertical_blank = true;
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        self.sound_timer = self.sound_timer.saturating_sub(1);
+    }
+
+    /// 声音定时器不为零时蜂鸣器应当发声
+    pub fn is_sound_active(&self) -> bool {
+        self.sound_timer > 0
+    }
+
+    /// XO-CHIP 音频模式缓冲区，128 个 1 bit 采样，高位在前
+    pub fn audio_pattern(&self) -> &[u8; AUDIO_PATTERN_LENGTH] {
+        &self.audio_pattern
+    }
+
+    /// XO-CHIP 音高寄存器
+    pub fn pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    /// 音频模式的播放速率（每秒采样数）：4000 * 2 ^ ((pitch - 64) / 48)
+    pub fn audio_playback_rate(&self) -> f64 {
+        4000.0 * 2f64.powf((self.pitch as f64 - 64.0) / 48.0)
+    }
+
+    /// 当前作用的平面，bit 0 为第一平面，bit 1 为第二平面
+    pub fn plane_mask(&self) -> u8 {
+        self.plane_mask
+    }
+
+    /// 内存大小，4KB 或 XO-CHIP 模式下的 64KB
+    pub fn memory_size(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// 保存完整的机器状态
+    ///
+    /// 存档以 `CH8S` 文件头和格式版本号开始，之后依次是兼容性配置、内存、寄存器、栈、
+    /// 定时器、键盘及等待状态、屏幕和各扩展的状态。
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut writer = StateWriter::new();
+        writer.u8(self.quirks.to_bits());
+        writer.u32(self.memory.len() as u32);
+        writer.bytes(&self.memory);
+        writer.bytes(&self.data_register);
+        writer.u16(self.address_register);
+        writer.u16(self.program_counter);
+        for &address in &self.stack {
+            writer.u16(address);
+        }
+        writer.u8(self.stack_pointer as u8);
+        writer.u8(self.delay_timer);
+        writer.u8(self.sound_timer);
+        let keys = self.keyboard.iter().enumerate().fold(0u16, |keys, (key, &pressed)| keys | (pressed as u16) << key);
+        writer.u16(keys);
+        writer.bool(self.keyboard_waiting);
+        writer.u8(self.keyboard_register as u8);
+        let pressed = self.keys_pressed_while_waiting.iter().enumerate();
+        writer.u16(pressed.fold(0u16, |keys, (key, &pressed)| keys | (pressed as u16) << key));
+        writer.u16(self.screen_width as u16);
+        writer.u16(self.screen_height as u16);
+        writer.bytes(&self.screen);
+        writer.u8(self.plane_mask);
+        writer.bool(self.vertical_blank);
+        writer.bytes(&self.rpl_flags);
+        writer.bool(self.exited);
+        writer.bytes(&self.audio_pattern);
+        writer.u8(self.pitch);
+        writer.u32(self.random_state);
+        writer.u16(self.font_address as u16);
+        writer.finish()
+    }
+
+    /// 从 `save_state` 生成的存档恢复机器状态
+    ///
+    /// 存档无效时返回 `Chip8Error::InvalidState`，此时机器状态不会被修改。
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), Chip8Error> {
+        let mut reader = StateReader::new(data)?;
+        let mut chip8 = Chip8::new_with(Quirks::from_bits(reader.u8()?));
+        let memory_size = reader.u32()? as usize;
+        if memory_size != chip8.memory.len() {
+            return Err(Chip8Error::InvalidState("memory size does not match mode"));
+        }
+        chip8.memory.copy_from_slice(reader.bytes(memory_size)?);
+        chip8.data_register.copy_from_slice(reader.bytes(16)?);
+        chip8.address_register = reader.u16()?;
+        chip8.program_counter = reader.u16()?;
+        for address in chip8.stack.iter_mut() {
+            *address = reader.u16()?;
+        }
+        chip8.stack_pointer = reader.u8()? as usize;
+        if chip8.stack_pointer > chip8.stack.len() {
+            return Err(Chip8Error::InvalidState("stack pointer out of range"));
+        }
+        chip8.delay_timer = reader.u8()?;
+        chip8.sound_timer = reader.u8()?;
+        let keys = reader.u16()?;
+        for (key, pressed) in chip8.keyboard.iter_mut().enumerate() {
+            *pressed = keys & (1 << key) != 0;
+        }
+        chip8.keyboard_waiting = reader.bool()?;
+        chip8.keyboard_register = reader.u8()? as usize & 0x0F;
+        let keys = reader.u16()?;
+        for (key, pressed) in chip8.keys_pressed_while_waiting.iter_mut().enumerate() {
+            *pressed = keys & (1 << key) != 0;
+        }
+        let width = reader.u16()? as usize;
+        let height = reader.u16()? as usize;
+        if (width, height) != (SCREEN_WIDTH, SCREEN_HEIGHT) && (width, height) != (HIRES_SCREEN_WIDTH, HIRES_SCREEN_HEIGHT) {
+            return Err(Chip8Error::InvalidState("unsupported resolution"));
+        }
+        chip8.set_resolution(width, height);
+        chip8.screen.copy_from_slice(reader.bytes(width * height)?);
+        chip8.plane_mask = reader.u8()? & 0x03;
+        chip8.vertical_blank = reader.bool()?;
+        chip8.rpl_flags.copy_from_slice(reader.bytes(16)?);
+        chip8.exited = reader.bool()?;
+        chip8.audio_pattern.copy_from_slice(reader.bytes(AUDIO_PATTERN_LENGTH)?);
+        chip8.pitch = reader.u8()?;
+        chip8.random_state = reader.u32()?;
+        chip8.font_address = reader.u16()? as usize;
+        if chip8.font_address + FONT_SIZE > chip8.memory.len() {
+            return Err(Chip8Error::InvalidState("font out of memory"));
+        }
+        reader.finish()?;
+        *self = chip8;
+        Ok(())
+    }
+
+    /// 读取 address 处的两个字节（大端）
+    ///
+    /// 地址超出内存（包括只剩一个字节）时返回 `Chip8Error::PcOutOfBounds`
+    fn read_word(&self, address: u16) -> Result<u16, Chip8Error> {
+        let address = address as usize;
+        if address + 1 >= self.memory.len() {
+            return Err(Chip8Error::PcOutOfBounds(address as u16));
         }
+        Ok((self.memory[address] as u16) << 8 | (self.memory[address + 1] as u16))
     }
 
     /// 获取指令
-    fn get_opcode(&self) -> u16 {
-        return (self.memory[self.program_counter as usize] as u16) << 8 | (self.memory[self.program_counter as usize + 1] as u16);
+    fn get_opcode(&self) -> Result<u16, Chip8Error> {
+        self.read_word(self.program_counter)
+    }
+
+    /// 跳过下一条指令，XO-CHIP 的 F000 NNNN 占用 4 个字节
+    fn skip_next_instruction(&mut self) {
+        let next = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        self.program_counter = match self.read_word(next) {
+            Ok(0xF000) => next.wrapping_add(4),
+            _ => next.wrapping_add(INSTRUCTION_LENGTH),
+        };
+    }
+
+    /// 读取 I + offset 处的内存，超出内存时回绕
+    fn memory_at_i(&self, offset: usize) -> u8 {
+        self.memory[(self.address_register as usize + offset) % self.memory.len()]
+    }
+
+    /// 写入 I + offset 处的内存，超出内存时回绕
+    fn set_memory_at_i(&mut self, offset: usize, value: u8) {
+        let address = (self.address_register as usize + offset) % self.memory.len();
+        self.memory[address] = value;
+    }
+
+    /// 在当前平面上绘制一个精灵，返回是否发生碰撞
+    fn draw_sprite(&mut self, address: usize, start_x: usize, start_y: usize, rows: usize, cols: usize, plane: u8) -> bool {
+        let (width, height) = (self.screen_width, self.screen_height);
+        let mut collision = false;
+        for row in 0..rows {
+            let mut py = start_y + row;
+            if py >= height {
+                if self.quirks.clip_sprites {
+                    break;
+                }
+                py %= height;
+            }
+            let row_address = address + row * cols / 8;
+            let mut sprite = (self.memory[row_address % self.memory.len()] as u16) << 8;
+            if cols == 16 {
+                sprite |= self.memory[(row_address + 1) % self.memory.len()] as u16;
+            }
+            for col in 0..cols {
+                let mut px = start_x + col;
+                if px >= width {
+                    if self.quirks.clip_sprites {
+                        break;
+                    }
+                    px %= width;
+                }
+                if sprite & (0x8000 >> col) != 0 {
+                    let pixel = &mut self.screen[py * width + px];
+                    collision |= *pixel & plane != 0;
+                    *pixel ^= plane;
+                    self.display_dirty = true;
+                }
+            }
+        }
+        collision
+    }
+
+    /// 将选中的平面移动 (dx, dy) 个像素，移出屏幕的像素被丢弃
+    fn scroll(&mut self, dx: isize, dy: isize) {
+        let (width, height) = (self.screen_width as isize, self.screen_height as isize);
+        let mask = self.plane_mask;
+        let old = self.screen.clone();
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (x - dx, y - dy);
+                let moved = if (0..width).contains(&sx) && (0..height).contains(&sy) {
+                    old[(sy * width + sx) as usize] & mask
+                } else {
+                    0
+                };
+                let index = (y * width + x) as usize;
+                self.screen[index] = (old[index] & !mask) | moved;
+            }
+        }
+        self.display_dirty = true;
+    }
+
+    /// 滚动距离：SUPER-CHIP 1.1 在低分辨率下按高分辨率像素滚动，实际距离减半
+    fn scroll_distance(&self, n: isize) -> isize {
+        if self.quirks.schip_lores && !self.is_hires() {
+            n / 2
+        } else {
+            n
+        }
+    }
+
+    /// 生成下一个随机字节
+    fn next_random(&mut self) -> u8 {
+        let mut x = self.random_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.random_state = x;
+        (x >> 24) as u8
+    }
+
+    /// 执行一条指令：取指、解码、执行
+    ///
+    /// 返回本次执行的指令信息，便于嵌入方逐条驱动并观察 CPU。
+    /// 出错时机器状态保持不变，不会推进 PC。
+    pub fn step(&mut self) -> Result<StepInfo, Chip8Error> {
+        let pc = self.program_counter;
+        let opcode = self.get_opcode()?;
+        let instruction = if opcode == 0xF000 {
+            Instruction::decode_long(opcode, self.read_word(pc.wrapping_add(INSTRUCTION_LENGTH))?)?
+        } else {
+            Instruction::decode(opcode)?
+        };
+        // 非 XO-CHIP 模式下这些操作码没有定义
+        if instruction.is_xochip() && !self.quirks.xochip {
+            return Err(Chip8Error::UnknownOpcode(opcode));
+        }
+        self.execute(instruction)?;
+        Ok(StepInfo { pc, opcode, instruction })
     }
 
-    /// 执行指令
-    fn exec_opcode(&mut self) {
-        let opcode = self.get_opcode();
-        match opcode & 0xF000 {
-            0x0000 => match opcode {
-                0x00E0 => self.cls(),
-                0x00EE => self.ret(),
-                _ => panic!("opcode {:#X} is bad", opcode),
-            },
-            0x1000 => self.jp(),
-            _ => {}
+    /// 执行已解码的指令
+    fn execute(&mut self, instruction: Instruction) -> Result<(), Chip8Error> {
+        match instruction {
+            Instruction::Cls => self.cls(),
+            Instruction::Ret => self.ret(),
+            Instruction::Scd(n) => self.scd(n),
+            Instruction::Scr => self.scr(),
+            Instruction::Scl => self.scl(),
+            Instruction::Exit => self.exit(),
+            Instruction::Low => self.low(),
+            Instruction::High => self.high(),
+            Instruction::Jp(nnn) => self.jp(nnn),
+            Instruction::Call(nnn) => self.call(nnn),
+            Instruction::SeVxByte { x, nn } => self.se_vx_byte(x, nn),
+            Instruction::SneVxByte { x, nn } => self.sne_vx_byte(x, nn),
+            Instruction::SeVxVy { x, y } => self.se_vx_vy(x, y),
+            Instruction::SaveVxVy { x, y } => self.save_vx_vy(x, y),
+            Instruction::LoadVxVy { x, y } => self.load_vx_vy(x, y),
+            Instruction::LdVxByte { x, nn } => self.ld_vx_byte(x, nn),
+            Instruction::AddVxByte { x, nn } => self.add_vx_byte(x, nn),
+            Instruction::LdVxVy { x, y } => self.ld_vx_vy(x, y),
+            Instruction::OrVxVy { x, y } => self.or_vx_vy(x, y),
+            Instruction::AndVxVy { x, y } => self.and_vx_vy(x, y),
+            Instruction::XorVxVy { x, y } => self.xor_vx_vy(x, y),
+            Instruction::AddVxVy { x, y } => self.add_vx_vy(x, y),
+            Instruction::SubVxVy { x, y } => self.sub_vx_vy(x, y),
+            Instruction::ShrVxVy { x, y } => self.shr_vx_vy(x, y),
+            Instruction::SubnVxVy { x, y } => self.subn_vx_vy(x, y),
+            Instruction::ShlVxVy { x, y } => self.shl_vx_vy(x, y),
+            Instruction::SneVxVy { x, y } => self.sne_vx_vy(x, y),
+            Instruction::LdIAddr(nnn) => self.ld_i_addr(nnn),
+            Instruction::JpV0Addr(nnn) => self.jp_v0_addr(nnn),
+            Instruction::RndVxByte { x, nn } => self.rnd_vx_byte(x, nn),
+            Instruction::DrwVxVyNibble { x, y, n } => self.drw_vx_vy_nibble(x, y, n),
+            Instruction::SkpVx { x } => self.skp_vx(x),
+            Instruction::SknpVx { x } => self.sknp_vx(x),
+            Instruction::LdILong(nnnn) => self.ld_i_long(nnnn),
+            Instruction::Plane(n) => self.plane(n),
+            Instruction::Audio => self.audio(),
+            Instruction::LdVxDt { x } => self.ld_vx_dt(x),
+            Instruction::LdVxK { x } => self.ld_vx_k(x),
+            Instruction::LdDtVx { x } => self.ld_dt_vx(x),
+            Instruction::LdStVx { x } => self.ld_st_vx(x),
+            Instruction::AddIVx { x } => self.add_i_vx(x),
+            Instruction::LdFVx { x } => self.ld_f_vx(x),
+            Instruction::LdHfVx { x } => self.ld_hf_vx(x),
+            Instruction::LdBVx { x } => self.ld_b_vx(x),
+            Instruction::PitchVx { x } => self.pitch_vx(x),
+            Instruction::LdIVx { x } => self.ld_i_vx(x),
+            Instruction::LdVxI { x } => self.ld_vx_i(x),
+            Instruction::LdRVx { x } => self.ld_r_vx(x),
+            Instruction::LdVxR { x } => self.ld_vx_r(x),
         }
     }
 }
 
+/// 使用默认兼容性配置
+impl Default for Chip8 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// 实现指令
 impl Instructions for Chip8 {
-    fn cls(&mut self) {
-        for y in 0..SCREEN_HEIGHT {
-            for x in 0..SCREEN_WIDTH {
-                self.screen[y][x] = 0;
-            }
-        }
-        self.program_counter += INSTRUCTION_LENGTH;
+    fn cls(&mut self) -> Result<(), Chip8Error> {
+        let mask = self.plane_mask;
+        self.screen.iter_mut().for_each(|pixel| *pixel &= !mask);
+        self.display_dirty = true;
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
     }
 
-    fn ret(&mut self) {
+    fn ret(&mut self) -> Result<(), Chip8Error> {
+        if self.stack_pointer == 0 {
+            return Err(Chip8Error::StackUnderflow);
+        }
         self.stack_pointer -= 1;
-        self.program_counter = self.stack[self.stack_pointer as usize];
-        self.program_counter += INSTRUCTION_LENGTH;
+        // 栈中保存的是 CALL 之后的指令地址
+        self.program_counter = self.stack[self.stack_pointer];
+        Ok(())
+    }
+
+    fn scd(&mut self, n: u8) -> Result<(), Chip8Error> {
+        self.scroll(0, self.scroll_distance(n as isize));
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn scr(&mut self) -> Result<(), Chip8Error> {
+        self.scroll(self.scroll_distance(4), 0);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn scl(&mut self) -> Result<(), Chip8Error> {
+        self.scroll(self.scroll_distance(-4), 0);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
     }
 
-    fn jp(&mut self) {
-        let nnn = self.get_opcode() & 0x0FFF;
+    fn exit(&mut self) -> Result<(), Chip8Error> {
+        // 不推进 PC，之后的执行都停留在这条指令上
+        self.exited = true;
+        Ok(())
+    }
+
+    fn low(&mut self) -> Result<(), Chip8Error> {
+        self.set_resolution(SCREEN_WIDTH, SCREEN_HEIGHT);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn high(&mut self) -> Result<(), Chip8Error> {
+        self.set_resolution(HIRES_SCREEN_WIDTH, HIRES_SCREEN_HEIGHT);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn jp(&mut self, nnn: u16) -> Result<(), Chip8Error> {
         self.program_counter = nnn;
+        Ok(())
     }
 
-    fn call(&mut self) {
-        self.stack[self.stack_pointer as usize] = self.program_counter + INSTRUCTION_LENGTH;
+    fn call(&mut self, nnn: u16) -> Result<(), Chip8Error> {
+        if self.stack_pointer >= self.stack.len() {
+            return Err(Chip8Error::StackOverflow);
+        }
+        self.stack[self.stack_pointer] = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
         self.stack_pointer += 1;
-        self.program_counter = self.get_opcode() & 0x0FFF;
+        self.program_counter = nnn;
+        Ok(())
     }
 
-    fn se_vx_byte(&mut self) {
-        let opcode = self.get_opcode();
-        let x = (opcode & 0x0F00) >> 8;
-        let nn = (opcode & 0x00FF) as u8;
-        if self.memory[x as usize] == nn {
-            self.program_counter += INSTRUCTION_LENGTH;
+    fn se_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error> {
+        if self.v(x) == nn {
+            self.skip_next_instruction();
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
         }
-        self.program_counter += INSTRUCTION_LENGTH;
+        Ok(())
     }
 
-    fn sne_vx_byte(&mut self) {
-        let opcode = self.get_opcode();
-        let x = (opcode & 0x0F00) >> 8;
-        let nn = (opcode & 0x00FF) as u8;
-        if self.memory[x as usize] != nn {
-            self.program_counter += INSTRUCTION_LENGTH;
+    fn sne_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error> {
+        if self.v(x) != nn {
+            self.skip_next_instruction();
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
         }
-        self.program_counter += INSTRUCTION_LENGTH;
+        Ok(())
     }
 
-    fn se_vx_vy(&mut self) {
-        let opcode = self.get_opcode();
-        let x = (opcode & 0x0F00) >> 8;
-        let y = (opcode & 0x00F0) >> 4;
-        if self.memory[x as usize] == self.memory[y as usize] {
-            self.program_counter += INSTRUCTION_LENGTH;
+    fn se_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        if self.v(x) == self.v(y) {
+            self.skip_next_instruction();
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
         }
-        self.program_counter += INSTRUCTION_LENGTH;
+        Ok(())
     }
 
-    fn ld_vx_byte(&mut self) {
-        let opcode = self.get_opcode();
-        let x = (opcode & 0x0F00) >> 8;
-        let nn = (opcode & 0x00FF) as u8;
-        self.memory[x as usize] = nn;
-        self.program_counter += INSTRUCTION_LENGTH;
+    fn save_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        // X 大于 Y 时按相反的顺序保存
+        for (offset, register) in register_range(x, y).enumerate() {
+            self.set_memory_at_i(offset, self.v(register));
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn load_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        for (offset, register) in register_range(x, y).enumerate() {
+            self.set_v(register, self.memory_at_i(offset));
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error> {
+        self.set_v(x, nn);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn add_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error> {
+        // 7XNN 溢出时回绕，并且不影响 VF
+        self.set_v(x, (Wrapping(self.v(x)) + Wrapping(nn)).0);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        self.set_v(x, self.v(y));
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn or_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        self.set_v(x, self.v(x) | self.v(y));
+        if self.quirks.logic_resets_vf {
+            self.set_v(0xF, 0);
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn and_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        self.set_v(x, self.v(x) & self.v(y));
+        if self.quirks.logic_resets_vf {
+            self.set_v(0xF, 0);
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn xor_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        self.set_v(x, self.v(x) ^ self.v(y));
+        if self.quirks.logic_resets_vf {
+            self.set_v(0xF, 0);
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn add_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        let (result, carry) = self.v(x).overflowing_add(self.v(y));
+        // 先写结果再写 VF，保证 X 为 F 时 VF 保存的是标志位
+        self.set_v(x, result);
+        self.set_v(0xF, carry as u8);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn sub_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        let (result, borrow) = self.v(x).overflowing_sub(self.v(y));
+        self.set_v(x, result);
+        self.set_v(0xF, !borrow as u8);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn shr_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        let value = if self.quirks.shift_uses_vy { self.v(y) } else { self.v(x) };
+        let flag = value & 0x01;
+        self.set_v(x, value >> 1);
+        self.set_v(0xF, flag);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn subn_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        let (result, borrow) = self.v(y).overflowing_sub(self.v(x));
+        self.set_v(x, result);
+        self.set_v(0xF, !borrow as u8);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn shl_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        let value = if self.quirks.shift_uses_vy { self.v(y) } else { self.v(x) };
+        let flag = (value & 0x80) >> 7;
+        self.set_v(x, value << 1);
+        self.set_v(0xF, flag);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn sne_vx_vy(&mut self, x: usize, y: usize) -> Result<(), Chip8Error> {
+        if self.v(x) != self.v(y) {
+            self.skip_next_instruction();
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        }
+        Ok(())
+    }
+
+    fn ld_i_addr(&mut self, nnn: u16) -> Result<(), Chip8Error> {
+        self.address_register = nnn;
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn jp_v0_addr(&mut self, nnn: u16) -> Result<(), Chip8Error> {
+        let offset = if self.quirks.jump_uses_vx { self.v((nnn >> 8) as usize) } else { self.v(0) };
+        self.program_counter = nnn + offset as u16;
+        Ok(())
+    }
+
+    fn rnd_vx_byte(&mut self, x: usize, nn: u8) -> Result<(), Chip8Error> {
+        let value = self.next_random() & nn;
+        self.set_v(x, value);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn drw_vx_vy_nibble(&mut self, x: usize, y: usize, n: u8) -> Result<(), Chip8Error> {
+        // 等待垂直同步时不推进 PC，下一帧再绘制
+        if self.quirks.display_wait && !self.vertical_blank {
+            return Ok(());
+        }
+        self.vertical_blank = false;
+        // 起始坐标超出屏幕时总是回绕，精灵本身超出屏幕的部分按配置裁剪或回绕
+        let start_x = self.v(x) as usize % self.screen_width;
+        let start_y = self.v(y) as usize % self.screen_height;
+        // N 为 0 时绘制 16 x 16 的精灵，每行两个字节；SUPER-CHIP 1.1 低分辨率下为 8 x 16
+        let (rows, cols) = match n {
+            0 if self.quirks.schip_lores && !self.is_hires() => (16, 8),
+            0 => (16, 16),
+            n => (n as usize, 8),
+        };
+        // 选中多个平面时，每个平面依次使用 I 之后的下一个精灵
+        let mut address = self.address_register as usize;
+        let mut collision = false;
+        for plane in [1, 2] {
+            if self.plane_mask & plane != 0 {
+                collision |= self.draw_sprite(address, start_x, start_y, rows, cols, plane);
+                address += rows * cols / 8;
+            }
+        }
+        self.set_v(0xF, collision as u8);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn skp_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        if self.keyboard[(self.v(x) & 0x0F) as usize] {
+            self.skip_next_instruction();
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        }
+        Ok(())
+    }
+
+    fn sknp_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        if !self.keyboard[(self.v(x) & 0x0F) as usize] {
+            self.skip_next_instruction();
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        }
+        Ok(())
+    }
+
+    fn ld_i_long(&mut self, nnnn: u16) -> Result<(), Chip8Error> {
+        self.address_register = nnnn;
+        self.program_counter = self.program_counter.wrapping_add(4);
+        Ok(())
+    }
+
+    fn plane(&mut self, n: u8) -> Result<(), Chip8Error> {
+        self.plane_mask = n & 0x03;
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn audio(&mut self) -> Result<(), Chip8Error> {
+        for offset in 0..AUDIO_PATTERN_LENGTH {
+            self.audio_pattern[offset] = self.memory_at_i(offset);
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_vx_dt(&mut self, x: usize) -> Result<(), Chip8Error> {
+        self.set_v(x, self.delay_timer);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_vx_k(&mut self, x: usize) -> Result<(), Chip8Error> {
+        // 与 COSMAC VIP 一致，按键松开时才完成输入（见 release_key），
+        // 在此之前不推进 PC，下一次执行仍停留在这条指令上
+        if !self.keyboard_waiting {
+            self.keys_pressed_while_waiting = [false; 16];
+        }
+        self.keyboard_waiting = true;
+        self.keyboard_register = x;
+        Ok(())
+    }
+
+    fn ld_dt_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        self.delay_timer = self.v(x);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_st_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        self.sound_timer = self.v(x);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn add_i_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        let i = Wrapping(self.address_register) + Wrapping(self.v(x) as u16);
+        self.address_register = i.0;
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_f_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        // 每个字符占 5 个字节
+        self.address_register = (self.font_address + (self.v(x) & 0x0F) as usize * 5) as u16;
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_hf_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        // 每个字符占 10 个字节
+        self.address_register = (self.font_address + FONT_SET.len() + (self.v(x) & 0x0F) as usize * 10) as u16;
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_b_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        let value = self.v(x);
+        self.set_memory_at_i(0, value / 100);
+        self.set_memory_at_i(1, value / 10 % 10);
+        self.set_memory_at_i(2, value % 10);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn pitch_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        self.pitch = self.v(x);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_i_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        for offset in 0..=x {
+            self.set_memory_at_i(offset, self.v(offset));
+        }
+        if self.quirks.load_store_increments_i {
+            self.address_register = self.address_register.wrapping_add(x as u16 + 1);
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_vx_i(&mut self, x: usize) -> Result<(), Chip8Error> {
+        for offset in 0..=x {
+            self.set_v(offset, self.memory_at_i(offset));
+        }
+        if self.quirks.load_store_increments_i {
+            self.address_register = self.address_register.wrapping_add(x as u16 + 1);
+        }
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_r_vx(&mut self, x: usize) -> Result<(), Chip8Error> {
+        self.rpl_flags[..=x].copy_from_slice(&self.data_register[..=x]);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+
+    fn ld_vx_r(&mut self, x: usize) -> Result<(), Chip8Error> {
+        self.data_register[..=x].copy_from_slice(&self.rpl_flags[..=x]);
+        self.program_counter = self.program_counter.wrapping_add(INSTRUCTION_LENGTH);
+        Ok(())
+    }
+}
+
+/// 5XY2 / 5XY3 访问的寄存器序列，从 X 到 Y，X 大于 Y 时倒序
+fn register_range(x: usize, y: usize) -> Box<dyn Iterator<Item = usize>> {
+    if x <= y {
+        Box::new(x..=y)
+    } else {
+        Box::new((y..=x).rev())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::{BIG_FONT_ADDRESS, BIG_FONT_SET, CHIP8_MEMORY, VIP_FONT_SET, XOCHIP_MEMORY};
+
+    /// 在当前 PC 处写入指令并执行
+    fn exec(chip8: &mut Chip8, opcode: u16) {
+        let pc = chip8.program_counter as usize;
+        chip8.memory[pc] = (opcode >> 8) as u8;
+        chip8.memory[pc + 1] = opcode as u8;
+        chip8.step().unwrap();
+    }
+
+    /// 用互不相同的值填充寄存器，便于发现被误改的寄存器
+    fn setup() -> Chip8 {
+        let mut chip8 = Chip8::new();
+        for x in 0..16 {
+            chip8.set_v(x, 0x10 + x as u8);
+        }
+        chip8
+    }
+
+    /// 断言除 `changed` 之外的寄存器都保持原值，并且字体区未被改写
+    fn assert_only_changed(before: &Chip8, after: &Chip8, changed: &[usize]) {
+        for x in 0..16 {
+            if !changed.contains(&x) {
+                assert_eq!(before.v(x), after.v(x), "V{:X} should not change", x);
+            }
+        }
+        assert_eq!(&after.memory[..FONT_SET.len()], &FONT_SET[..]);
+    }
+
+    fn snapshot(chip8: &Chip8) -> Chip8 {
+        let mut copy = Chip8::new();
+        copy.data_register = chip8.data_register;
+        copy.memory = chip8.memory.clone();
+        copy
+    }
+
+    #[test]
+    fn se_vx_byte_skips_when_equal() {
+        let mut chip8 = setup();
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x3313);
+        assert_eq!(chip8.program_counter, 0x204);
+        assert_only_changed(&before, &chip8, &[]);
+    }
+
+    #[test]
+    fn se_vx_byte_does_not_skip_when_different() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0x3300);
+        assert_eq!(chip8.program_counter, 0x202);
+    }
+
+    #[test]
+    fn sne_vx_byte_skips_when_different() {
+        let mut chip8 = setup();
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x4300);
+        assert_eq!(chip8.program_counter, 0x204);
+        assert_only_changed(&before, &chip8, &[]);
+    }
+
+    #[test]
+    fn sne_vx_byte_does_not_skip_when_equal() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0x4313);
+        assert_eq!(chip8.program_counter, 0x202);
+    }
+
+    #[test]
+    fn se_vx_vy_compares_registers() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0x5120);
+        assert_eq!(chip8.program_counter, 0x202);
+
+        let mut chip8 = setup();
+        chip8.set_v(2, chip8.v(1));
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x5120);
+        assert_eq!(chip8.program_counter, 0x204);
+        assert_only_changed(&before, &chip8, &[]);
+    }
+
+    #[test]
+    fn sne_vx_vy_compares_registers() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0x9120);
+        assert_eq!(chip8.program_counter, 0x204);
+
+        let mut chip8 = setup();
+        chip8.set_v(2, chip8.v(1));
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x9120);
+        assert_eq!(chip8.program_counter, 0x202);
+        assert_only_changed(&before, &chip8, &[]);
+    }
+
+    #[test]
+    fn ld_i_and_add_i_set_address_register() {
+        let mut chip8 = setup();
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0xA123);
+        assert_eq!(chip8.address_register, 0x123);
+        exec(&mut chip8, 0xF31E);
+        assert_eq!(chip8.address_register, 0x123 + 0x13);
+        assert_only_changed(&before, &chip8, &[]);
+    }
+
+    #[test]
+    fn jp_v0_adds_v0_to_address() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0xB300);
+        assert_eq!(chip8.program_counter, 0x310);
+    }
+
+    #[test]
+    fn rnd_masks_random_byte() {
+        let mut chip8 = setup();
+        let before = snapshot(&chip8);
+        for _ in 0..32 {
+            exec(&mut chip8, 0xC10F);
+            assert!(chip8.v(1) <= 0x0F);
+        }
+        exec(&mut chip8, 0xC100);
+        assert_eq!(chip8.v(1), 0);
+        assert_only_changed(&before, &chip8, &[1]);
+    }
+
+    #[test]
+    fn timer_instructions_copy_vx() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0xF315);
+        exec(&mut chip8, 0xF418);
+        assert_eq!((chip8.delay_timer, chip8.sound_timer), (0x13, 0x14));
+        chip8.delay_timer = 0x12;
+        exec(&mut chip8, 0xF507);
+        assert_eq!(chip8.v(5), 0x12);
+    }
+
+    #[test]
+    fn ld_f_vx_points_at_font_digit() {
+        let mut chip8 = setup();
+        chip8.set_v(1, 0xA);
+        exec(&mut chip8, 0xF129);
+        assert_eq!(chip8.address_register, 0xA * 5);
+        assert_eq!(chip8.memory[chip8.address_register as usize..][..5], FONT_SET[50..55]);
+
+        // 只使用 VX 的低 4 位
+        chip8.set_v(1, 0x1A);
+        exec(&mut chip8, 0xF129);
+        assert_eq!(chip8.address_register, 0xA * 5);
+    }
+
+    #[test]
+    fn ld_vx_byte_writes_only_vx() {
+        let mut chip8 = setup();
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x6AAB);
+        assert_eq!(chip8.v(0xA), 0xAB);
+        assert_eq!(chip8.program_counter, 0x202);
+        assert_only_changed(&before, &chip8, &[0xA]);
+    }
+
+    #[test]
+    fn add_vx_byte_wraps_without_touching_vf() {
+        let mut chip8 = setup();
+        chip8.set_v(4, 0xFF);
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x7402);
+        assert_eq!(chip8.v(4), 0x01);
+        assert_only_changed(&before, &chip8, &[4]);
+    }
+
+    #[test]
+    fn logic_ops_write_only_vx() {
+        for (opcode, expected) in [(0x8120, 0x12), (0x8121, 0x11 | 0x12), (0x8122, 0x11 & 0x12), (0x8123, 0x11 ^ 0x12)] {
+            let mut chip8 = setup();
+            let before = snapshot(&chip8);
+            exec(&mut chip8, opcode);
+            assert_eq!(chip8.v(1), expected, "opcode {:#06X}", opcode);
+            assert_only_changed(&before, &chip8, &[1]);
+        }
+    }
+
+    #[test]
+    fn add_vx_vy_sets_carry() {
+        let mut chip8 = setup();
+        chip8.set_v(1, 0xF0);
+        chip8.set_v(2, 0x20);
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x8124);
+        assert_eq!(chip8.v(1), 0x10);
+        assert_eq!(chip8.v(0xF), 1);
+        assert_only_changed(&before, &chip8, &[1, 0xF]);
+
+        exec(&mut chip8, 0x8124);
+        assert_eq!(chip8.v(1), 0x30);
+        assert_eq!(chip8.v(0xF), 0);
+    }
+
+    #[test]
+    fn sub_and_subn_set_not_borrow() {
+        let mut chip8 = setup();
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x8215);
+        assert_eq!(chip8.v(2), 0x01);
+        assert_eq!(chip8.v(0xF), 1);
+        assert_only_changed(&before, &chip8, &[2, 0xF]);
+
+        let mut chip8 = setup();
+        exec(&mut chip8, 0x8217);
+        assert_eq!(chip8.v(2), 0xFF);
+        assert_eq!(chip8.v(0xF), 0);
+    }
+
+    #[test]
+    fn shifts_store_shifted_out_bit_in_vf() {
+        let mut chip8 = setup();
+        chip8.set_v(3, 0x81);
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x8306);
+        assert_eq!(chip8.v(3), 0x40);
+        assert_eq!(chip8.v(0xF), 1);
+        assert_only_changed(&before, &chip8, &[3, 0xF]);
+
+        let mut chip8 = setup();
+        chip8.set_v(3, 0x81);
+        exec(&mut chip8, 0x830E);
+        assert_eq!(chip8.v(3), 0x02);
+        assert_eq!(chip8.v(0xF), 1);
+    }
+
+    #[test]
+    fn flag_wins_when_vf_is_the_destination() {
+        let mut chip8 = setup();
+        chip8.set_v(0xF, 0xFF);
+        chip8.set_v(1, 0x01);
+        exec(&mut chip8, 0x8F14);
+        assert_eq!(chip8.v(0xF), 1);
+    }
+
+    #[test]
+    fn store_and_load_registers_use_memory_at_i() {
+        let mut chip8 = setup();
+        chip8.address_register = 0x300;
+        exec(&mut chip8, 0xF255);
+        assert_eq!(&chip8.memory[0x300..0x304], &[0x10, 0x11, 0x12, 0x00]);
+
+        let mut chip8 = setup();
+        chip8.address_register = 0x300;
+        chip8.memory[0x300..0x303].copy_from_slice(&[1, 2, 3]);
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0xF165);
+        assert_eq!((chip8.v(0), chip8.v(1), chip8.v(2)), (1, 2, 0x12));
+        assert_only_changed(&before, &chip8, &[0, 1]);
+    }
+
+    #[test]
+    fn bcd_writes_digits() {
+        let mut chip8 = setup();
+        chip8.set_v(5, 254);
+        chip8.address_register = 0x300;
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0xF533);
+        assert_eq!(&chip8.memory[0x300..0x303], &[2, 5, 4]);
+        assert_only_changed(&before, &chip8, &[]);
+    }
+
+    #[test]
+    fn step_reports_decoded_instruction() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x6A, 0x42, 0x12, 0x00]).unwrap();
+        let info = chip8.step().unwrap();
+        assert_eq!(info, StepInfo { pc: 0x200, opcode: 0x6A42, instruction: Instruction::LdVxByte { x: 0xA, nn: 0x42 } });
+        let info = chip8.step().unwrap();
+        assert_eq!(info.instruction, Instruction::Jp(0x200));
+        assert_eq!(chip8.program_counter, 0x200);
+    }
+
+    #[test]
+    fn step_rejects_unknown_opcode() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x80, 0x0F]).unwrap();
+        assert_eq!(chip8.step(), Err(Chip8Error::UnknownOpcode(0x800F)));
+        assert_eq!(chip8.program_counter, 0x200);
+    }
+
+    #[test]
+    fn ret_on_empty_stack_is_an_error() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x00, 0xEE]).unwrap();
+        assert_eq!(chip8.step(), Err(Chip8Error::StackUnderflow));
+        assert_eq!(chip8.program_counter, 0x200);
+    }
+
+    #[test]
+    fn ret_resumes_after_call() {
+        let mut chip8 = Chip8::new();
+        // 2206 7001 0000 00EE
+        chip8.load_rom(&[0x22, 0x06, 0x70, 0x01, 0x00, 0x00, 0x00, 0xEE]).unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.program_counter, 0x206);
+        assert_eq!(chip8.stack(), [0x202]);
+        chip8.step().unwrap();
+        assert_eq!(chip8.program_counter, 0x202);
+        assert!(chip8.stack().is_empty());
+    }
+
+    #[test]
+    fn call_past_stack_depth_is_an_error() {
+        let mut chip8 = Chip8::new();
+        // 2200：不断调用自身
+        chip8.load_rom(&[0x22, 0x00]).unwrap();
+        for _ in 0..16 {
+            chip8.step().unwrap();
+        }
+        assert_eq!(chip8.step(), Err(Chip8Error::StackOverflow));
+        assert_eq!(chip8.stack_pointer, 16);
+    }
+
+    #[test]
+    fn mutation_api_changes_state() {
+        let mut chip8 = Chip8::new();
+        chip8.set_program_counter(0x300);
+        chip8.set_address_register(0x400);
+        chip8.set_delay_timer(3);
+        chip8.set_sound_timer(4);
+        chip8.memory_mut()[0x300..0x302].copy_from_slice(&[0x00, 0xEE]);
+        chip8.set_stack(&[0x208, 0x20C]).unwrap();
+        assert_eq!(chip8.set_stack(&[0; 17]), Err(Chip8Error::StackOverflow));
+        assert_eq!(chip8.stack(), [0x208, 0x20C]);
+
+        // 0x300 处的 RET 返回到栈顶的地址
+        chip8.step().unwrap();
+        assert_eq!(chip8.program_counter(), 0x20C);
+        assert_eq!(chip8.stack(), [0x208]);
+        assert_eq!((chip8.address_register(), chip8.delay_timer(), chip8.sound_timer()), (0x400, 3, 4));
+
+        chip8.take_display_dirty();
+        chip8.set_pixel(5, 6, 3);
+        chip8.set_pixel(64, 0, 1);
+        assert!(chip8.pixel(5, 6));
+        assert_eq!(chip8.screen()[6 * 64 + 5], 3);
+        assert_eq!(chip8.screen().iter().filter(|&&p| p != 0).count(), 1);
+        assert!(chip8.is_display_dirty());
+    }
+
+    #[test]
+    fn load_rom_rejects_oversized_rom() {
+        let mut chip8 = Chip8::new();
+        assert!(chip8.load_rom(&[0xAA; CHIP8_MEMORY - 0x200]).is_ok());
+        assert_eq!(
+            chip8.load_rom(&[0; CHIP8_MEMORY - 0x200 + 1]),
+            Err(Chip8Error::RomTooLarge { size: CHIP8_MEMORY - 0x200 + 1, max: CHIP8_MEMORY - 0x200 })
+        );
+    }
+
+    #[test]
+    fn load_rom_at_sets_load_address_and_entry_point() {
+        let mut chip8 = Chip8::new();
+        // ETI 660：从 0x600 加载并执行
+        chip8.load_rom_at(&[0x6A, 0x2A], 0x600, 0x600).unwrap();
+        assert_eq!(chip8.memory()[0x600..0x602], [0x6A, 0x2A]);
+        chip8.step().unwrap();
+        assert_eq!((chip8.v(0xA), chip8.program_counter()), (0x2A, 0x602));
+
+        // CHIP-8 HIRES：从 0x200 加载，从 0x2C0 开始执行
+        let mut chip8 = Chip8::new();
+        chip8.load_rom_at(&[0x12, 0x60], 0x200, 0x2C0).unwrap();
+        assert_eq!(chip8.program_counter(), 0x2C0);
+
+        let max = CHIP8_MEMORY - 0x600;
+        assert_eq!(chip8.load_rom_at(&vec![0; max + 1], 0x600, 0x600), Err(Chip8Error::RomTooLarge { size: max + 1, max }));
+        assert_eq!(chip8.load_rom_at(&[0x00], 0x1000, 0x200), Err(Chip8Error::RomTooLarge { size: 1, max: 0 }));
+        assert_eq!(chip8.load_rom_at(&[], 0x200, 0x1000), Err(Chip8Error::PcOutOfBounds(0x1000)));
+        assert_eq!(chip8.program_counter(), 0x2C0);
+    }
+
+    #[test]
+    fn pc_past_end_of_memory_is_an_error() {
+        let mut chip8 = Chip8::new();
+        // BFFF：V0 = 1 时跳转到 0x1000
+        chip8.set_v(0, 1);
+        chip8.load_rom(&[0xBF, 0xFF]).unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.step(), Err(Chip8Error::PcOutOfBounds(0x1000)));
+    }
+
+    #[test]
+    fn timers_count_down_to_zero() {
+        let mut chip8 = Chip8::new();
+        chip8.delay_timer = 2;
+        chip8.sound_timer = 1;
+        assert!(chip8.is_sound_active());
+        chip8.tick_timers();
+        assert_eq!((chip8.delay_timer, chip8.sound_timer), (1, 0));
+        assert!(!chip8.is_sound_active());
+        chip8.tick_timers();
+        chip8.tick_timers();
+        assert_eq!((chip8.delay_timer, chip8.sound_timer), (0, 0));
+    }
+
+    #[test]
+    fn skp_and_sknp_follow_keyboard() {
+        let mut chip8 = setup();
+        chip8.set_v(1, 0xA);
+        chip8.press_key(0xA);
+        exec(&mut chip8, 0xE19E);
+        assert_eq!(chip8.program_counter, 0x204);
+        exec(&mut chip8, 0xE1A1);
+        assert_eq!(chip8.program_counter, 0x206);
+
+        chip8.release_key(0xA);
+        exec(&mut chip8, 0xE19E);
+        assert_eq!(chip8.program_counter, 0x208);
+        exec(&mut chip8, 0xE1A1);
+        assert_eq!(chip8.program_counter, 0x20C);
+    }
+
+    #[test]
+    fn ld_vx_k_waits_for_key_release() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0xF30A);
+        assert!(chip8.is_waiting_for_key());
+        assert_eq!(chip8.program_counter, 0x200);
+
+        // 按下时仍在等待，再次执行也不会前进
+        chip8.press_key(0x7);
+        chip8.step().unwrap();
+        assert!(chip8.is_waiting_for_key());
+        assert_eq!(chip8.program_counter, 0x200);
+
+        chip8.release_key(0x7);
+        assert!(!chip8.is_waiting_for_key());
+        assert_eq!(chip8.v(3), 0x7);
+        assert_eq!(chip8.program_counter, 0x202);
+    }
+
+    #[test]
+    fn set_keys_reports_releases_to_ld_vx_k() {
+        let mut chip8 = setup();
+        let mut keys = [false; 16];
+        keys[0xC] = true;
+        chip8.set_keys(keys);
+        exec(&mut chip8, 0xF50A);
+        assert!(chip8.is_key_pressed(0xC));
+        // 等待之前就已按住的按键松开时不结束等待
+        chip8.set_keys([false; 16]);
+        assert!(chip8.is_waiting_for_key());
+        chip8.set_keys(keys);
+        chip8.set_keys(keys);
+        chip8.set_keys([false; 16]);
+        assert_eq!(chip8.v(5), 0xC);
+        assert!(!chip8.is_waiting_for_key());
+    }
+
+    #[test]
+    fn ld_vx_k_ignores_keys_held_before_waiting() {
+        let mut chip8 = setup();
+        chip8.press_key(0x1);
+        exec(&mut chip8, 0xF20A);
+        chip8.press_key(0x2);
+        chip8.release_key(0x1);
+        assert!(chip8.is_waiting_for_key());
+
+        // 存档保留等待期间按下的按键
+        let mut restored = Chip8::new();
+        restored.load_state(&chip8.save_state()).unwrap();
+        restored.release_key(0x2);
+        assert!(!restored.is_waiting_for_key());
+        assert_eq!(restored.v(2), 0x2);
+    }
+
+    #[test]
+    fn releasing_unpressed_key_does_not_end_wait() {
+        let mut chip8 = setup();
+        exec(&mut chip8, 0xF00A);
+        chip8.release_key(0x1);
+        chip8.press_key(0x10);
+        assert!(chip8.is_waiting_for_key());
+    }
+
+    /// 把一个精灵放到 0x300 并在 (vx, vy) 处绘制
+    fn draw(chip8: &mut Chip8, vx: u8, vy: u8, sprite: &[u8]) {
+        chip8.memory[0x300..0x300 + sprite.len()].copy_from_slice(sprite);
+        chip8.address_register = 0x300;
+        chip8.set_v(0, vx);
+        chip8.set_v(1, vy);
+        exec(chip8, 0xD010 | sprite.len() as u16);
+    }
+
+    #[test]
+    fn drw_xors_pixels_and_reports_collision() {
+        let mut chip8 = Chip8::new();
+        draw(&mut chip8, 2, 3, &[0b1100_0000]);
+        assert!(chip8.pixel(2, 3) && chip8.pixel(3, 3) && !chip8.pixel(4, 3));
+        assert_eq!(chip8.v(0xF), 0);
+
+        draw(&mut chip8, 3, 3, &[0b1000_0000]);
+        assert!(chip8.pixel(2, 3) && !chip8.pixel(3, 3));
+        assert_eq!(chip8.v(0xF), 1);
+    }
+
+    #[test]
+    fn drw_wraps_start_position() {
+        let mut chip8 = Chip8::new();
+        draw(&mut chip8, SCREEN_WIDTH as u8 + 1, SCREEN_HEIGHT as u8 + 2, &[0x80]);
+        assert!(chip8.pixel(1, 2));
+    }
+
+    #[test]
+    fn drw_clips_or_wraps_at_edges() {
+        let mut chip8 = Chip8::new();
+        draw(&mut chip8, 62, 31, &[0xF0, 0xF0]);
+        assert!(chip8.pixel(62, 31) && chip8.pixel(63, 31));
+        assert!(!chip8.pixel(0, 31) && !chip8.pixel(0, 0));
+
+        let mut chip8 = Chip8::new_with(Quirks { clip_sprites: false, ..Quirks::default() });
+        draw(&mut chip8, 62, 31, &[0xF0, 0xF0]);
+        assert!(chip8.pixel(0, 31) && chip8.pixel(1, 31));
+        assert!(chip8.pixel(62, 0) && chip8.pixel(1, 0));
+    }
+
+    #[test]
+    fn display_dirty_tracks_screen_changes() {
+        let mut chip8 = Chip8::new();
+        assert!(chip8.take_display_dirty());
+        assert!(!chip8.is_display_dirty());
+
+        draw(&mut chip8, 0, 0, &[0x00]);
+        assert!(!chip8.take_display_dirty());
+        draw(&mut chip8, 0, 0, &[0x80]);
+        assert!(chip8.take_display_dirty());
+        exec(&mut chip8, 0x00E0);
+        assert!(chip8.take_display_dirty());
+        assert_eq!(chip8.screen()[0], 0);
+    }
+
+    #[test]
+    fn shift_quirk_selects_source_register() {
+        let mut chip8 = Chip8::new_with(Quirks::cosmac_vip());
+        chip8.set_v(1, 0x81);
+        chip8.set_v(2, 0x06);
+        exec(&mut chip8, 0x8126);
+        assert_eq!((chip8.v(1), chip8.v(0xF)), (0x03, 0));
+        exec(&mut chip8, 0x812E);
+        assert_eq!((chip8.v(1), chip8.v(0xF)), (0x0C, 0));
     }
 
-    // ??? 检查是否存在bug
-    fn add_vx_byte(&mut self) {
-        let opcode = self.get_opcode();
-        // let x = (opcode & 0x0F00) >> 8;
-        // let nn = Wrapping((opcode & 0x00FF) as u8);
-        // let x_val = Wrapping(self.memory[x as usize]);
-        // self.memory[x as usize] = (x_val + nn).0;
-        // self.program_counter += 2;
-        let x = (opcode & 0x0F00) >> 8;
-        let nn = (opcode & 0x00FF) as u8;
-        self.memory[x as usize] += nn;
-        self.program_counter += INSTRUCTION_LENGTH;
+    #[test]
+    fn jump_quirk_uses_vx() {
+        let mut chip8 = setup();
+        chip8.set_quirks(Quirks::chip48());
+        exec(&mut chip8, 0xB300);
+        assert_eq!(chip8.program_counter, 0x300 + 0x13);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn load_store_quirk_increments_i() {
+        let mut chip8 = Chip8::new_with(Quirks::cosmac_vip());
+        chip8.address_register = 0x300;
+        exec(&mut chip8, 0xF255);
+        assert_eq!(chip8.address_register, 0x303);
+        exec(&mut chip8, 0xF065);
+        assert_eq!(chip8.address_register, 0x304);
+    }
+
+    #[test]
+    fn logic_quirk_resets_vf() {
+        let mut chip8 = setup();
+        chip8.set_quirks(Quirks::cosmac_vip());
+        exec(&mut chip8, 0x8121);
+        assert_eq!(chip8.v(0xF), 0);
+    }
+
+    #[test]
+    fn display_wait_quirk_draws_once_per_frame() {
+        let mut chip8 = Chip8::new_with(Quirks::cosmac_vip());
+        draw(&mut chip8, 0, 0, &[0x80]);
+        assert_eq!(chip8.program_counter, 0x202);
+        exec(&mut chip8, 0xD011);
+        assert_eq!(chip8.program_counter, 0x202);
+        chip8.tick_timers();
+        chip8.step().unwrap();
+        assert_eq!(chip8.program_counter, 0x204);
+        assert!(!chip8.pixel(0, 0));
+    }
+
+    #[test]
+    fn high_and_low_resize_framebuffer() {
+        let mut chip8 = Chip8::new();
+        exec(&mut chip8, 0x00FF);
+        assert!(chip8.is_hires());
+        assert_eq!((chip8.screen_width(), chip8.screen_height()), (128, 64));
+        assert_eq!(chip8.screen().len(), 128 * 64);
+        draw(&mut chip8, 100, 60, &[0x80]);
+        assert!(chip8.pixel(100, 60));
+
+        exec(&mut chip8, 0x00FE);
+        assert!(!chip8.is_hires());
+        assert_eq!(chip8.screen().len(), 64 * 32);
+        assert!(!chip8.screen().contains(&1));
+    }
+
+    #[test]
+    fn drw_with_zero_rows_draws_16x16_sprite() {
+        let mut chip8 = Chip8::new();
+        exec(&mut chip8, 0x00FF);
+        let mut sprite = [0u8; 32];
+        sprite[0] = 0x80;
+        sprite[31] = 0x01;
+        chip8.memory[0x300..0x320].copy_from_slice(&sprite);
+        chip8.address_register = 0x300;
+        chip8.set_v(0, 10);
+        chip8.set_v(1, 20);
+        exec(&mut chip8, 0xD010);
+        assert!(chip8.pixel(10, 20));
+        assert!(chip8.pixel(25, 35));
+        assert_eq!(chip8.screen().iter().filter(|&&p| p == 1).count(), 2);
+    }
+
+    #[test]
+    fn schip_lores_draws_8x16_sprite() {
+        let mut chip8 = Chip8::new_with(Quirks { schip_lores: true, ..Quirks::default() });
+        let mut sprite = [0u8; 16];
+        sprite[0] = 0x80;
+        sprite[15] = 0x01;
+        chip8.memory[0x300..0x310].copy_from_slice(&sprite);
+        chip8.address_register = 0x300;
+        chip8.set_v(0, 10);
+        chip8.set_v(1, 5);
+        exec(&mut chip8, 0xD010);
+        assert!(chip8.pixel(10, 5));
+        assert!(chip8.pixel(17, 20));
+        assert_eq!(chip8.screen().iter().filter(|&&p| p == 1).count(), 2);
+    }
+
+    #[test]
+    fn schip_lores_halves_scroll_distance() {
+        let mut chip8 = Chip8::new_with(Quirks { schip_lores: true, ..Quirks::default() });
+        draw(&mut chip8, 10, 5, &[0x80]);
+        exec(&mut chip8, 0x00C4);
+        assert!(chip8.pixel(10, 7));
+        exec(&mut chip8, 0x00FB);
+        assert!(chip8.pixel(12, 7));
+        exec(&mut chip8, 0x00FC);
+        exec(&mut chip8, 0x00FC);
+        assert!(chip8.pixel(8, 7));
+        // 高分辨率下滚动距离不变
+        exec(&mut chip8, 0x00FF);
+        draw(&mut chip8, 10, 5, &[0x80]);
+        exec(&mut chip8, 0x00FB);
+        assert!(chip8.pixel(14, 5));
+    }
+
+    #[test]
+    fn superchip_preset_uses_schip_lores() {
+        assert!(Quirks::superchip().schip_lores);
+        assert!(!Quirks::chip48().schip_lores);
+        let mut chip8 = Chip8::new_with(Quirks::from_name("schip").unwrap());
+        chip8.address_register = 0x300;
+        chip8.memory[0x300..0x320].fill(0xFF);
+        exec(&mut chip8, 0xD010);
+        assert_eq!(chip8.screen().iter().filter(|&&p| p == 1).count(), 8 * 16);
+    }
+
+    #[test]
+    fn scroll_moves_pixels() {
+        let mut chip8 = Chip8::new();
+        draw(&mut chip8, 10, 5, &[0x80]);
+        exec(&mut chip8, 0x00C3);
+        assert!(chip8.pixel(10, 8) && !chip8.pixel(10, 5));
+        exec(&mut chip8, 0x00FB);
+        assert!(chip8.pixel(14, 8));
+        exec(&mut chip8, 0x00FC);
+        exec(&mut chip8, 0x00FC);
+        assert!(chip8.pixel(6, 8));
+        assert_eq!(chip8.screen().iter().filter(|&&p| p == 1).count(), 1);
+    }
+
+    #[test]
+    fn big_font_and_rpl_flags() {
+        let mut chip8 = setup();
+        chip8.set_v(0, 7);
+        exec(&mut chip8, 0xF030);
+        assert_eq!(chip8.address_register as usize, BIG_FONT_ADDRESS + 70);
+        assert_eq!(chip8.memory[chip8.address_register as usize], BIG_FONT_SET[70]);
+
+        exec(&mut chip8, 0xF375);
+        for x in 0..4 {
+            chip8.set_v(x, 0);
+        }
+        exec(&mut chip8, 0xF285);
+        assert_eq!((chip8.v(0), chip8.v(1), chip8.v(2), chip8.v(3)), (7, 0x11, 0x12, 0));
+    }
+
+    #[test]
+    fn font_can_be_replaced_and_moved() {
+        let mut chip8 = setup();
+        chip8.set_font(&Font::vip(), 0x50).unwrap();
+        assert_eq!(chip8.font_address(), 0x50);
+        assert!(chip8.memory[FONT_ADDRESS..0x50].iter().all(|&byte| byte == 0));
+
+        chip8.set_v(0, 1);
+        exec(&mut chip8, 0xF029);
+        assert_eq!(chip8.address_register, 0x55);
+        assert_eq!(chip8.memory[0x55..0x5A], VIP_FONT_SET[5..10]);
+        exec(&mut chip8, 0xF030);
+        assert_eq!(chip8.address_register, 0x50 + 80 + 10);
+
+        // 位置随存档保存
+        let state = chip8.save_state();
+        let mut restored = Chip8::new();
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.font_address(), 0x50);
+
+        assert_eq!(chip8.set_font(&Font::default(), 0x0F20), Err(Chip8Error::FontOutOfBounds(0x0F20)));
+        assert_eq!(chip8.font_address(), 0x50);
+    }
+
+    #[test]
+    fn exit_halts_program() {
+        let mut chip8 = Chip8::new();
+        exec(&mut chip8, 0x00FD);
+        assert!(chip8.has_exited());
+        assert_eq!(chip8.program_counter, 0x200);
+    }
+
+    #[test]
+    fn xochip_mode_has_64k_memory() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        assert_eq!(chip8.memory_size(), XOCHIP_MEMORY);
+        assert!(chip8.load_rom(&vec![0; XOCHIP_MEMORY - 0x200]).is_ok());
+
+        chip8.set_quirks(Quirks::default());
+        assert_eq!(chip8.memory_size(), CHIP8_MEMORY);
+    }
+
+    #[test]
+    fn shrinking_memory_keeps_state_in_range() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        chip8.set_font(&Font::superchip(), 0x8000).unwrap();
+        chip8.program_counter = 0x1234;
+        chip8.address_register = 0xE000;
+        chip8.stack[0] = 0x2202;
+        chip8.stack_pointer = 1;
+
+        chip8.set_quirks(Quirks::default());
+        assert_eq!(chip8.font_address(), FONT_ADDRESS as u16);
+        assert_eq!(chip8.memory[FONT_ADDRESS..FONT_ADDRESS + FONT_SIZE], Font::superchip().to_bytes()[..]);
+        assert_eq!(chip8.program_counter, 0x234);
+        assert_eq!(chip8.address_register, 0);
+        assert_eq!(chip8.stack[0], 0x202);
+        exec(&mut chip8, 0xF029);
+        assert_eq!(chip8.address_register, FONT_ADDRESS as u16);
+    }
+
+    #[test]
+    fn xochip_opcodes_need_xochip_mode() {
+        for opcode in [0xF000u16, 0x5012, 0x5013, 0xF101, 0xF002, 0xF03A] {
+            let mut chip8 = Chip8::new();
+            chip8.load_rom(&opcode.to_be_bytes()).unwrap();
+            assert_eq!(chip8.step(), Err(Chip8Error::UnknownOpcode(opcode)));
+            assert_eq!(chip8.program_counter, 0x200);
+        }
+    }
+
+    #[test]
+    fn pc_wraps_at_end_of_64k_memory() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        chip8.memory[0xFFFE..].copy_from_slice(&[0x60, 0x01]);
+        chip8.set_program_counter(0xFFFE);
+        chip8.step().unwrap();
+        assert_eq!((chip8.v(0), chip8.program_counter()), (1, 0x0000));
+
+        // CALL 压入的返回地址同样回绕
+        chip8.memory[0xFFFE..].copy_from_slice(&[0x23, 0x00]);
+        chip8.set_program_counter(0xFFFE);
+        chip8.step().unwrap();
+        assert_eq!(chip8.stack(), [0x0000]);
+
+        // F000 NNNN 的地址在 0xFFFE
+        chip8.memory[0xFFFC..].copy_from_slice(&[0xF0, 0x00, 0x12, 0x34]);
+        chip8.set_program_counter(0xFFFC);
+        chip8.step().unwrap();
+        assert_eq!((chip8.address_register, chip8.program_counter()), (0x1234, 0x0000));
+    }
+
+    #[test]
+    fn ld_i_long_reads_following_word() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        chip8.load_rom(&[0xF0, 0x00, 0xE0, 0x00, 0xF0, 0x55]).unwrap();
+        let info = chip8.step().unwrap();
+        assert_eq!(info.instruction, Instruction::LdILong(0xE000));
+        assert_eq!(chip8.address_register, 0xE000);
+        assert_eq!(chip8.program_counter, 0x204);
+        chip8.set_v(0, 0xAB);
+        chip8.step().unwrap();
+        assert_eq!(chip8.memory[0xE000], 0xAB);
+    }
+
+    #[test]
+    fn skip_jumps_over_long_instruction() {
+        let mut chip8 = setup();
+        chip8.load_rom(&[0x30, 0x10, 0xF0, 0x00, 0x12, 0x34]).unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.program_counter, 0x206);
+    }
+
+    #[test]
+    fn save_and_load_register_ranges() {
+        let mut chip8 = setup();
+        chip8.set_quirks(Quirks::xochip());
+        chip8.address_register = 0x300;
+        exec(&mut chip8, 0x5242);
+        assert_eq!(&chip8.memory[0x300..0x304], &[0x12, 0x13, 0x14, 0]);
+        assert_eq!(chip8.address_register, 0x300);
+
+        exec(&mut chip8, 0x5422);
+        assert_eq!(&chip8.memory[0x300..0x303], &[0x14, 0x13, 0x12]);
+
+        chip8.memory[0x300..0x302].copy_from_slice(&[0xAA, 0xBB]);
+        let before = snapshot(&chip8);
+        exec(&mut chip8, 0x5783);
+        assert_eq!((chip8.v(7), chip8.v(8)), (0xAA, 0xBB));
+        assert_only_changed(&before, &chip8, &[7, 8]);
+    }
+
+    #[test]
+    fn planes_draw_separate_sprites() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        exec(&mut chip8, 0xF301);
+        assert_eq!(chip8.plane_mask(), 3);
+        // 两个平面各一个 1 字节精灵：第一平面点亮 (0, 0)，第二平面点亮 (0, 0) 和 (1, 0)
+        chip8.memory[0x300..0x302].copy_from_slice(&[0x80, 0xC0]);
+        chip8.address_register = 0x300;
+        exec(&mut chip8, 0xD001);
+        assert_eq!(&chip8.screen()[..3], &[3, 2, 0]);
+
+        exec(&mut chip8, 0xF201);
+        exec(&mut chip8, 0x00E0);
+        assert_eq!(&chip8.screen()[..3], &[1, 0, 0]);
+        assert!(chip8.pixel(0, 0));
+    }
+
+    #[test]
+    fn scroll_only_moves_selected_planes() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        exec(&mut chip8, 0xF301);
+        chip8.memory[0x300..0x302].copy_from_slice(&[0x80, 0x80]);
+        chip8.address_register = 0x300;
+        exec(&mut chip8, 0xD001);
+        exec(&mut chip8, 0xF201);
+        exec(&mut chip8, 0x00C1);
+        assert_eq!(chip8.screen()[0], 1);
+        assert_eq!(chip8.screen()[chip8.screen_width()], 2);
+    }
+
+    #[test]
+    fn audio_pattern_and_pitch() {
+        let mut chip8 = setup();
+        chip8.set_quirks(Quirks::xochip());
+        chip8.address_register = 0x300;
+        for i in 0..16 {
+            chip8.memory[0x300 + i] = i as u8;
+        }
+        exec(&mut chip8, 0xF002);
+        assert_eq!(chip8.audio_pattern()[15], 15);
+        assert_eq!(chip8.audio_playback_rate(), 4000.0);
+
+        chip8.set_v(1, 112);
+        exec(&mut chip8, 0xF13A);
+        assert_eq!(chip8.pitch(), 112);
+        assert_eq!(chip8.audio_playback_rate(), 8000.0);
+    }
+
+    #[test]
+    fn save_state_round_trips() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        chip8.load_rom(&[0x00, 0xFF, 0x22, 0x06, 0x12, 0x04, 0x60, 0x07, 0xF0, 0x29, 0xD0, 0x15, 0xF0, 0x0A, 0x00, 0xEE]).unwrap();
+        for _ in 0..7 {
+            chip8.step().unwrap();
+        }
+        chip8.press_key(3);
+        chip8.tick_timers();
+        let state = chip8.save_state();
+        assert_eq!(&state[..4], b"CH8S");
+
+        let mut restored = Chip8::new();
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.save_state(), state);
+        assert!(restored.is_hires() && restored.is_waiting_for_key());
+        assert_eq!(restored.screen(), chip8.screen());
+
+        // 恢复后继续执行与原机器一致
+        chip8.release_key(3);
+        restored.release_key(3);
+        chip8.step().unwrap();
+        restored.step().unwrap();
+        assert_eq!(restored.save_state(), chip8.save_state());
+    }
+
+    #[test]
+    fn load_state_rejects_bad_data() {
+        let mut chip8 = setup();
+        let state = chip8.save_state();
+        let before = chip8.save_state();
+
+        assert_eq!(chip8.load_state(b"CH"), Err(Chip8Error::InvalidState("unexpected end of data")));
+        assert_eq!(chip8.load_state(&state[..state.len() - 1]), Err(Chip8Error::InvalidState("unexpected end of data")));
+        let mut bad = state.clone();
+        bad[0] = b'X';
+        assert_eq!(chip8.load_state(&bad), Err(Chip8Error::InvalidState("bad magic header")));
+        let mut bad = state.clone();
+        bad[5] = 99;
+        assert_eq!(chip8.load_state(&bad), Err(Chip8Error::InvalidState("unsupported version")));
+        let mut bad = state.clone();
+        bad.push(0);
+        assert_eq!(chip8.load_state(&bad), Err(Chip8Error::InvalidState("trailing data")));
+        assert_eq!(chip8.save_state(), before);
+    }
+}
@@ -1,24 +1,36 @@
 /// Chip8 是单色 64 x 32 像素的显示屏
 // 屏幕宽
-pub(crate) const SCREEN_WIDTH: usize = 64;
+pub const SCREEN_WIDTH: usize = 64;
 // 屏幕高
-pub(crate) const SCREEN_HEIGHT: usize = 32;
+pub const SCREEN_HEIGHT: usize = 32;
+/// SUPER-CHIP 高分辨率模式为 128 x 64 像素
+// 高分辨率屏幕宽
+pub const HIRES_SCREEN_WIDTH: usize = 128;
+// 高分辨率屏幕高
+pub const HIRES_SCREEN_HEIGHT: usize = 64;
 // 4KB 内存
-pub(crate) const CHIP8_MEMORY: usize = 4096;
+pub const CHIP8_MEMORY: usize = 4096;
+// XO-CHIP 的 64KB 内存
+pub const XOCHIP_MEMORY: usize = 65536;
+/// 程序的加载地址
+pub const PROGRAM_START: u16 = 0x200;
+/// ETI 660 计算机上的程序从 0x600 加载
+pub const ETI660_PROGRAM_START: u16 = 0x600;
 /// CHIP-8 程序严格基于十六进制。
 ///
 /// 这意味着 CHIP-8 程序的格式与高级语言的基于文本的格式几乎没有相似之处。
 ///
 /// 每条 CHIP-8 指令的长度为两个字节，并使用四个十六进制数字表示，指令以大端方式存储在内存中。
-pub(crate) const INSTRUCTION_LENGTH: u16 = 2;
+pub const INSTRUCTION_LENGTH: u16 = 2;
 
 /// 字符集
 /// CHIP-8 包含内置字体实用程序，允许使用 DXYN 指令简单地输出字符。
 ///
 /// 所有十六进制数字（0-9，A-F）都有相应的数据已经存储在解释器的内存中。
+/// 这是 CHIP-48 的字体，SUPER-CHIP 和 Octo 也使用它。
 ///
-/// 参阅：https://github.com/mattmikolay/chip-8/wiki/Mastering-CHIP%E2%80%908 Drawing Font
-pub(crate) const FONT_SET: [u8; 80] = [
+/// 参阅：<https://github.com/mattmikolay/chip-8/wiki/Mastering-CHIP%E2%80%908> Drawing Font
+pub const FONT_SET: [u8; 80] = [
     0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
     0x20, 0x60, 0x20, 0x20, 0x70, // 1
     0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
@@ -35,4 +47,87 @@ pub(crate) const FONT_SET: [u8; 80] = [
     0xE0, 0x90, 0x90, 0x90, 0xE0, // D
     0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
     0xF0, 0x80, 0xF0, 0x80, 0x80  // F
-];
\ No newline at end of file
+];
+/// COSMAC VIP 的字符集，"1" 带有上方的短横，"7" 是一条竖线，A - F 也与 CHIP-48 不同
+pub const VIP_FONT_SET: [u8; 80] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x60, 0x20, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0xA0, 0xA0, 0xF0, 0x20, 0x20, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x10, 0x10, 0x10, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xF0, 0x50, 0x70, 0x50, 0xF0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xF0, 0x50, 0x50, 0x50, 0xF0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80  // F
+];
+/// 小字体在内存中的默认起始地址
+pub const FONT_ADDRESS: usize = 0x000;
+
+/// Octo 的大字符集，每个字符 8 x 10 像素，供 FX30 使用
+pub const BIG_FONT_SET: [u8; 160] = [
+    0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, // 0
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF, // 1
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // 2
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 3
+    0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 5
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 6
+    0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, // 7
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 8
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0  // F
+];
+/// SUPER-CHIP 1.1 的大字符集，字符带有圆角
+///
+/// SUPER-CHIP 1.1 只提供 0-9，A-F 取自 Octo。
+pub const SCHIP_BIG_FONT_SET: [u8; 160] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+    0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0  // F
+];
+/// 大字体紧跟在小字体之后
+pub const BIG_FONT_ADDRESS: usize = FONT_ADDRESS + FONT_SET.len();
+/// 小字体和大字体一共占用的字节数
+pub const FONT_SIZE: usize = FONT_SET.len() + BIG_FONT_SET.len();
+
+/// 延迟定时器和声音定时器以 60Hz 的频率递减
+pub const TIMER_FREQUENCY: u32 = 60;
+/// 默认的 CPU 频率（每秒执行的指令数）
+pub const DEFAULT_CLOCK_RATE: u32 = 600;
+
+/// XO-CHIP 音频模式缓冲区的长度（128 位）
+pub const AUDIO_PATTERN_LENGTH: usize = 16;
+/// XO-CHIP 默认的音高寄存器值，对应 4000Hz 的采样播放速率
+pub const DEFAULT_PITCH: u8 = 64;
+
+/// 声音的默认采样率（Hz）
+pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
+/// 声音定时器非零时蜂鸣的默认频率（Hz）
+pub const DEFAULT_TONE_FREQUENCY: f32 = 440.0;
+/// 蜂鸣的默认音量，0.0 - 1.0
+pub const DEFAULT_VOLUME: f32 = 0.25;
@@ -0,0 +1,575 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::{self, Write as _};
+use std::io::{self, BufRead, Write};
+
+use crate::chip8::Chip8;
+use crate::constant::{ETI660_PROGRAM_START, TIMER_FREQUENCY};
+use crate::disasm::line_at;
+use crate::error::Chip8Error;
+use crate::instruction::Instruction;
+use crate::rewind::Rewind;
+
+const HELP: &str = "commands:
+  s, step [N]          execute N instructions (default 1)
+  n, next              execute one instruction, stepping over CALL
+  bk, back [N]         undo the last N step or next commands (default 1)
+  c, continue          run until a breakpoint, watchpoint, key wait or exit
+  w, wait              run until the program waits for a key, ignoring breakpoints
+  b, break [ADDR]      set a breakpoint at ADDR, or list breakpoints
+  d, delete ADDR       delete the breakpoint at ADDR
+  watch VX|ADDR        stop when register VX or memory at ADDR changes
+  unwatch VX|ADDR      delete a watchpoint
+  k, key K             press and release key K (0 - F)
+  r, regs              show registers, timers and stack
+  x, dump ADDR [LEN]   hexdump LEN bytes of memory (default 64)
+  l, list [ADDR]       disassemble around ADDR (default PC)
+  h, help              show this help
+  q, quit              quit
+addresses are hexadecimal; an empty line repeats the last command";
+
+/// `list` 在目标地址之前和之后显示的指令数
+const LIST_BEFORE: u16 = 4;
+const LIST_AFTER: u16 = 6;
+
+/// `back` 最多可以撤销的 `step` / `next` 命令数
+const HISTORY_DEPTH: usize = 1000;
+
+/// 观察点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watch {
+    /// 内存中的一个字节
+    Memory(u16),
+    /// 通用寄存器 VX
+    Register(usize),
+}
+
+impl Watch {
+    fn parse(text: &str) -> Option<Self> {
+        match text.strip_prefix(['v', 'V']) {
+            Some(x) if x.len() == 1 => usize::from_str_radix(x, 16).ok().map(Watch::Register),
+            _ => parse_address(text).map(Watch::Memory),
+        }
+    }
+
+    fn value(&self, chip8: &Chip8) -> u8 {
+        match *self {
+            Watch::Memory(address) => chip8.memory().get(address as usize).copied().unwrap_or(0),
+            Watch::Register(x) => chip8.v(x),
+        }
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Watch::Memory(address) => write!(f, "[{:04X}]", address),
+            Watch::Register(x) => write!(f, "V{:X}", x),
+        }
+    }
+}
+
+/// 程序停止运行的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// 执行完要求的指令数
+    Done,
+    /// 到达断点
+    Breakpoint(u16),
+    /// 观察的值发生变化
+    Watch { watch: Watch, old: u8, new: u8 },
+    /// 程序在等待按键
+    KeyWait,
+    /// 程序执行了 00FD
+    Exited,
+    /// 程序跳转到自身，不会再有任何变化
+    Loop(u16),
+    /// 执行出错
+    Error(Chip8Error),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Done => Ok(()),
+            Stop::Breakpoint(address) => write!(f, "breakpoint at {:04X}", address),
+            Stop::Watch { watch, old, new } => write!(f, "{} changed: {:02X} -> {:02X}", watch, old, new),
+            Stop::KeyWait => write!(f, "waiting for a key"),
+            Stop::Exited => write!(f, "program exited"),
+            Stop::Loop(address) => write!(f, "infinite loop at {:04X}", address),
+            Stop::Error(e) => write!(f, "error: {}", e),
+        }
+    }
+}
+
+/// 命令行调试器
+///
+/// 逐条执行指令，每执行 `clock_rate / 60` 条指令递减一次定时器，与 `Scheduler` 的节奏一致。
+pub struct Debugger {
+    chip8: Chip8,
+    breakpoints: BTreeSet<u16>,
+    watches: Vec<Watch>,
+    // 每帧执行的指令数
+    cycles_per_frame: u64,
+    // 已执行的指令数
+    cycles: u64,
+    // 每条 step / next 命令执行前的状态和已执行的指令数，用于后退
+    history: Rewind,
+    history_cycles: VecDeque<u64>,
+    // 空行时重复的命令
+    last_command: String,
+}
+
+impl Debugger {
+    /// 调试 `chip8`，`clock_rate` 为模拟的 CPU 频率（Hz）
+    pub fn new(chip8: Chip8, clock_rate: u32) -> Self {
+        Self {
+            chip8,
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+            cycles_per_frame: (clock_rate / TIMER_FREQUENCY).max(1) as u64,
+            cycles: 0,
+            history: Rewind::new(HISTORY_DEPTH, 1),
+            history_cycles: VecDeque::new(),
+            last_command: String::new(),
+        }
+    }
+
+    /// 从标准输入读取命令，直到 `quit` 或输入结束
+    pub fn repl(&mut self) -> io::Result<()> {
+        let stdin = io::stdin();
+        let mut stdout = io::stdout();
+        writeln!(stdout, "{}", self.current())?;
+        loop {
+            write!(stdout, "(chip8) ")?;
+            stdout.flush()?;
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            match self.command(&line) {
+                Some(output) => writeln!(stdout, "{}", output)?,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// 执行一条调试命令并返回输出，`quit` 时返回 None
+    pub fn command(&mut self, line: &str) -> Option<String> {
+        let line = match line.trim() {
+            "" => self.last_command.clone(),
+            line => line.to_string(),
+        };
+        self.last_command = line.clone();
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+        let output = match (command, args.as_slice()) {
+            ("" | "h" | "help", _) => HELP.to_string(),
+            ("q" | "quit", _) => return None,
+            ("s" | "step", []) => {
+                self.record();
+                self.report(1, true, |_| false)
+            }
+            ("s" | "step", [count]) => match count.parse() {
+                Ok(count) => {
+                    self.record();
+                    self.report(count, true, |_| false)
+                }
+                Err(_) => format!("invalid count: {}", count),
+            },
+            ("n" | "next", []) => {
+                self.record();
+                self.next()
+            }
+            ("bk" | "back", []) => self.back(1),
+            ("bk" | "back", [count]) => match count.parse() {
+                Ok(count) => self.back(count),
+                Err(_) => format!("invalid count: {}", count),
+            },
+            ("c" | "continue", []) => self.report(u64::MAX, true, |_| false),
+            ("w" | "wait", []) => self.report(u64::MAX, false, |_| false),
+            ("b" | "break", []) => self.breakpoints.iter().fold(String::from("breakpoints:"), |mut s, address| {
+                let _ = write!(s, " {:04X}", address);
+                s
+            }),
+            ("b" | "break", [address]) => match parse_address(address) {
+                Some(address) => {
+                    self.breakpoints.insert(address);
+                    format!("breakpoint at {:04X}", address)
+                }
+                None => format!("invalid address: {}", address),
+            },
+            ("d" | "delete", [address]) => match parse_address(address) {
+                Some(address) if self.breakpoints.remove(&address) => format!("deleted breakpoint at {:04X}", address),
+                _ => format!("no breakpoint at {}", address),
+            },
+            ("watch", [target]) => match Watch::parse(target) {
+                Some(watch) => {
+                    if !self.watches.contains(&watch) {
+                        self.watches.push(watch);
+                    }
+                    format!("watching {} = {:02X}", watch, watch.value(&self.chip8))
+                }
+                None => format!("invalid watch target: {}", target),
+            },
+            ("unwatch", [target]) => match Watch::parse(target) {
+                Some(watch) if self.watches.contains(&watch) => {
+                    self.watches.retain(|w| *w != watch);
+                    format!("deleted watchpoint on {}", watch)
+                }
+                _ => format!("no watchpoint on {}", target),
+            },
+            ("k" | "key", [key]) => match usize::from_str_radix(key, 16) {
+                Ok(key) if key < 16 => {
+                    self.chip8.press_key(key);
+                    self.chip8.release_key(key);
+                    format!("pressed key {:X}\n{}", key, self.current())
+                }
+                _ => format!("invalid key: {}", key),
+            },
+            ("r" | "regs", []) => self.registers(),
+            ("x" | "dump", [address]) => self.dump(address, "64"),
+            ("x" | "dump", [address, len]) => self.dump(address, len),
+            ("l" | "list", []) => self.list(self.chip8.program_counter()),
+            ("l" | "list", [address]) => match parse_address(address) {
+                Some(address) => self.list(address),
+                None => format!("invalid address: {}", address),
+            },
+            _ => format!("invalid command: {} (type help for a list of commands)", line),
+        };
+        Some(output)
+    }
+
+    /// 执行一条指令，返回是否需要停止
+    fn step(&mut self) -> Option<Stop> {
+        if self.chip8.has_exited() {
+            return Some(Stop::Exited);
+        }
+        let before: Vec<u8> = self.watches.iter().map(|watch| watch.value(&self.chip8)).collect();
+        let info = match self.chip8.step() {
+            Ok(info) => info,
+            Err(e) => return Some(Stop::Error(e)),
+        };
+        self.cycles += 1;
+        // 不使用 is_multiple_of，它需要 Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        let frame_end = self.cycles % self.cycles_per_frame == 0;
+        if frame_end {
+            self.chip8.tick_timers();
+        }
+        for (watch, old) in self.watches.iter().zip(before) {
+            let new = watch.value(&self.chip8);
+            if new != old {
+                return Some(Stop::Watch { watch: *watch, old, new });
+            }
+        }
+        if self.chip8.has_exited() {
+            return Some(Stop::Exited);
+        }
+        if self.chip8.is_waiting_for_key() {
+            return Some(Stop::KeyWait);
+        }
+        if info.instruction == Instruction::Jp(info.pc) {
+            return Some(Stop::Loop(info.pc));
+        }
+        None
+    }
+
+    /// 最多执行 `limit` 条指令，直到 `until` 返回 true 或者需要停止
+    ///
+    /// `stops` 为 false 时忽略断点和观察点。
+    fn resume(&mut self, limit: u64, stops: bool, until: impl Fn(&Chip8) -> bool) -> Stop {
+        for _ in 0..limit {
+            match self.step() {
+                Some(Stop::Watch { .. }) if !stops => {}
+                Some(stop) => return stop,
+                None => {}
+            }
+            let pc = self.chip8.program_counter();
+            if until(&self.chip8) {
+                break;
+            }
+            if stops && self.breakpoints.contains(&pc) {
+                return Stop::Breakpoint(pc);
+            }
+        }
+        Stop::Done
+    }
+
+    /// 运行并输出停止原因和当前指令
+    fn report(&mut self, limit: u64, stops: bool, until: impl Fn(&Chip8) -> bool) -> String {
+        let stop = self.resume(limit, stops, until);
+        match stop {
+            Stop::Done => self.current(),
+            stop => format!("{}\n{}", stop, self.current()),
+        }
+    }
+
+    /// 执行一条指令，遇到 CALL 时一直执行到子程序返回
+    fn next(&mut self) -> String {
+        let pc = self.chip8.program_counter();
+        let depth = self.chip8.stack().len();
+        match line_at(self.chip8.memory(), pc).and_then(|line| line.instruction) {
+            Some(Instruction::Call(_)) => self.report(u64::MAX, true, move |chip8| {
+                chip8.program_counter() == pc.wrapping_add(2) && chip8.stack().len() == depth
+            }),
+            _ => self.report(1, true, |_| false),
+        }
+    }
+
+    /// 保存 step / next 执行前的状态
+    ///
+    /// 只有这两个命令会保存，continue 等命令可能执行任意多条指令，逐条保存代价太高。
+    fn record(&mut self) {
+        self.history.push(&self.chip8);
+        self.history_cycles.push_back(self.cycles);
+        if self.history_cycles.len() > HISTORY_DEPTH {
+            self.history_cycles.pop_front();
+        }
+    }
+
+    /// 撤销最近的 `count` 条 step / next 命令
+    fn back(&mut self, count: u64) -> String {
+        for _ in 0..count {
+            if !self.history.rewind(&mut self.chip8) {
+                return format!("no earlier step recorded\n{}", self.current());
+            }
+            self.cycles = self.history_cycles.pop_back().unwrap_or(0);
+        }
+        self.current()
+    }
+
+    /// 当前指令
+    fn current(&self) -> String {
+        let pc = self.chip8.program_counter();
+        match line_at(self.chip8.memory(), pc) {
+            Some(line) => format!("=> {}", line),
+            None => format!("=> {:04X}  (outside memory)", pc),
+        }
+    }
+
+    fn registers(&self) -> String {
+        let chip8 = &self.chip8;
+        let mut output = String::new();
+        for (x, value) in chip8.registers().iter().enumerate() {
+            let separator = if x % 8 == 7 { '\n' } else { ' ' };
+            let _ = write!(output, "V{:X}={:02X}{}", x, value, separator);
+        }
+        let _ = writeln!(
+            output,
+            "PC={:04X} I={:04X} DT={:02X} ST={:02X} cycles={}",
+            chip8.program_counter(),
+            chip8.address_register(),
+            chip8.delay_timer(),
+            chip8.sound_timer(),
+            self.cycles
+        );
+        output.push_str("stack:");
+        if chip8.stack().is_empty() {
+            output.push_str(" (empty)");
+        }
+        for address in chip8.stack() {
+            let _ = write!(output, " {:04X}", address);
+        }
+        output
+    }
+
+    fn dump(&self, address: &str, len: &str) -> String {
+        let Some(start) = parse_address(address) else {
+            return format!("invalid address: {}", address);
+        };
+        let Ok(len) = len.parse::<usize>() else {
+            return format!("invalid length: {}", len);
+        };
+        let memory = self.chip8.memory();
+        let start = start as usize;
+        let end = (start + len).min(memory.len());
+        if start >= end {
+            return format!("address {:04X} is outside memory", start);
+        }
+        let mut output = String::new();
+        for (i, row) in memory[start..end].chunks(16).enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+            let _ = write!(output, "{:04X} ", start + i * 16);
+            for byte in row {
+                let _ = write!(output, " {:02X}", byte);
+            }
+            let text: String = row
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
+                .collect();
+            let _ = write!(output, "{:width$}  |{}|", "", text, width = (16 - row.len()) * 3);
+        }
+        output
+    }
+
+    /// 反汇编 `address` 附近的指令
+    ///
+    /// 无法确定之前的指令从哪里开始，因此按两字节对齐向前回溯。
+    fn list(&self, address: u16) -> String {
+        let memory = self.chip8.memory();
+        let mut lines = Vec::new();
+        let mut address = address.saturating_sub(LIST_BEFORE * 2);
+        for _ in 0..LIST_BEFORE + LIST_AFTER + 1 {
+            let Some(line) = line_at(memory, address) else { break };
+            let marker = if address == self.chip8.program_counter() {
+                "=>"
+            } else if self.breakpoints.contains(&address) {
+                " *"
+            } else {
+                "  "
+            };
+            lines.push(format!("{} {}", marker, line));
+            address = address.wrapping_add(line.bytes.len() as u16);
+        }
+        lines.join("\n")
+    }
+}
+
+/// 解析十六进制地址，可以带有 `0x` 前缀，`eti660` 表示 0x600
+///
+/// 调试器命令和命令行的地址选项使用相同的规则。
+pub fn parse_address(text: &str) -> Option<u16> {
+    if text.eq_ignore_ascii_case("eti660") {
+        return Some(ETI660_PROGRAM_START);
+    }
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    // from_str_radix 接受开头的 +
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u16::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::asm::assemble;
+    use crate::quirks::Quirks;
+
+    fn debugger(source: &str) -> Debugger {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&assemble(source, Path::new("test.8s")).unwrap()).unwrap();
+        Debugger::new(chip8, 600)
+    }
+
+    fn run(debugger: &mut Debugger, command: &str) -> String {
+        debugger.command(command).unwrap()
+    }
+
+    const PROGRAM: &str = "
+        start:  LD V0, 1
+                CALL sub
+                ADD V0, 1
+                LD V1, K
+                JP start
+        sub:    LD V2, 5
+                LD V3, 6
+                RET
+    ";
+
+    #[test]
+    fn steps_and_repeats_last_command() {
+        let mut debugger = debugger(PROGRAM);
+        assert_eq!(run(&mut debugger, "step"), "=> 0202  220A      CALL 0x20A");
+        assert_eq!(run(&mut debugger, ""), "=> 020A  6205      LD V2, 0x05");
+        assert_eq!(run(&mut debugger, "s 2"), "=> 020E  00EE      RET");
+        assert_eq!(debugger.chip8.stack(), [0x204]);
+    }
+
+    #[test]
+    fn next_steps_over_calls() {
+        let mut debugger = debugger(PROGRAM);
+        run(&mut debugger, "s");
+        assert_eq!(run(&mut debugger, "n"), "=> 0204  7001      ADD V0, 0x01");
+        assert_eq!(debugger.chip8.v(2), 5);
+        assert_eq!(debugger.chip8.v(3), 6);
+    }
+
+    #[test]
+    fn next_steps_over_call_at_end_of_memory() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        chip8.memory_mut()[0xFFFE..].copy_from_slice(&[0x23, 0x00]);
+        chip8.memory_mut()[0x300..0x302].copy_from_slice(&[0x00, 0xEE]);
+        chip8.set_program_counter(0xFFFE);
+        let mut debugger = Debugger::new(chip8, 600);
+        run(&mut debugger, "n");
+        assert_eq!(debugger.chip8.program_counter(), 0x0000);
+        assert!(debugger.chip8.stack().is_empty());
+    }
+
+    #[test]
+    fn back_undoes_step_and_next() {
+        let mut debugger = debugger(PROGRAM);
+        run(&mut debugger, "s");
+        run(&mut debugger, "n");
+        run(&mut debugger, "s 2");
+        assert_eq!(debugger.cycles, 7);
+        assert_eq!(run(&mut debugger, "back"), "=> 0204  7001      ADD V0, 0x01");
+        assert_eq!((debugger.chip8.v(2), debugger.cycles), (5, 5));
+        assert_eq!(run(&mut debugger, "bk"), "=> 0202  220A      CALL 0x20A");
+        assert_eq!(debugger.chip8.v(2), 0);
+        assert_eq!(run(&mut debugger, "bk 5"), "no earlier step recorded\n=> 0200  6001      LD V0, 0x01");
+        assert_eq!(debugger.cycles, 0);
+
+        // continue 不保存状态
+        run(&mut debugger, "c");
+        assert_eq!(run(&mut debugger, "bk"), "no earlier step recorded\n=> 0206  F10A      LD V1, K");
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints_stop_execution() {
+        let mut debugger = debugger(PROGRAM);
+        run(&mut debugger, "b 20C");
+        assert_eq!(run(&mut debugger, "c"), "breakpoint at 020C\n=> 020C  6306      LD V3, 0x06");
+        run(&mut debugger, "watch v0");
+        assert_eq!(run(&mut debugger, "c"), "V0 changed: 01 -> 02\n=> 0206  F10A      LD V1, K");
+        assert_eq!(run(&mut debugger, "unwatch V0"), "deleted watchpoint on V0");
+        assert_eq!(run(&mut debugger, "d 20c"), "deleted breakpoint at 020C");
+    }
+
+    #[test]
+    fn wait_runs_until_key_wait_and_key_resumes() {
+        let mut debugger = debugger(PROGRAM);
+        run(&mut debugger, "b 20C");
+        assert_eq!(run(&mut debugger, "wait"), "waiting for a key\n=> 0206  F10A      LD V1, K");
+        assert_eq!(run(&mut debugger, "key a"), "pressed key A\n=> 0208  1200      JP 0x200");
+        assert_eq!(debugger.chip8.v(1), 0xA);
+    }
+
+    #[test]
+    fn shows_registers_memory_and_listing() {
+        let mut debugger = debugger(PROGRAM);
+        run(&mut debugger, "s 2");
+        let registers = run(&mut debugger, "regs");
+        assert!(registers.starts_with("V0=01 V1=00"), "{}", registers);
+        assert!(registers.contains("PC=020A I=0000 DT=00 ST=00 cycles=2"), "{}", registers);
+        assert!(registers.ends_with("stack: 0204"), "{}", registers);
+        assert_eq!(run(&mut debugger, "x 0x200 4"), "0200  60 01 22 0A                                      |`.\".|");
+        let listing = run(&mut debugger, "list");
+        assert!(listing.contains("=> 020A  6205      LD V2, 0x05"), "{}", listing);
+        assert!(listing.starts_with("   0202  220A      CALL 0x20A"), "{}", listing);
+    }
+
+    #[test]
+    fn reports_self_jumps_and_bad_input() {
+        let mut debugger = debugger("loop: JP loop");
+        assert_eq!(run(&mut debugger, "c"), "infinite loop at 0200\n=> 0200  1200      JP 0x200");
+        assert_eq!(run(&mut debugger, "b xyz"), "invalid address: xyz");
+        assert!(run(&mut debugger, "frobnicate").starts_with("invalid command"));
+        assert_eq!(debugger.command("quit"), None);
+    }
+
+    #[test]
+    fn parses_addresses() {
+        assert_eq!(parse_address("2A0"), Some(0x2A0));
+        assert_eq!(parse_address("0x2a0"), Some(0x2A0));
+        assert_eq!(parse_address("ETI660"), Some(0x600));
+        assert_eq!(parse_address("+200"), None);
+        assert_eq!(parse_address("10000"), None);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::instruction::Instruction;
+
+/// 反汇编结果中的一行：一条指令或最多 `DATA_PER_LINE` 个数据字节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+    /// 数据行为 None
+    pub instruction: Option<Instruction>,
+}
+
+/// 每行数据的最大字节数
+const DATA_PER_LINE: usize = 4;
+
+/// rom 的反汇编结果
+///
+/// 从入口地址开始跟踪控制流，只有可能被执行到的字节才会解码为指令，其余字节按数据输出，
+/// 避免把精灵等数据误解码为指令。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    pub lines: Vec<Line>,
+    /// 跳转和调用的目标地址
+    pub labels: BTreeSet<u16>,
+}
+
+/// 反汇编加载到 `origin` 的 rom，从 `entry` 开始执行
+///
+/// 入口地址不在 rom 中时所有字节都按数据输出。
+pub fn disassemble(rom: &[u8], origin: u16, entry: u16) -> Disassembly {
+    let (instructions, labels) = trace(rom, origin, entry);
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < rom.len() {
+        let address = origin.wrapping_add(offset as u16);
+        if let Some(&instruction) = instructions.get(&offset) {
+            let end = offset + instruction.size() as usize;
+            lines.push(Line { address, bytes: rom[offset..end].to_vec(), instruction: Some(instruction) });
+            offset = end;
+            continue;
+        }
+        // 数据在下一条指令、下一个标签或满一行处断开
+        let start = offset;
+        offset += 1;
+        while offset < rom.len()
+            && offset - start < DATA_PER_LINE
+            && !instructions.contains_key(&offset)
+            && !labels.contains(&origin.wrapping_add(offset as u16))
+        {
+            offset += 1;
+        }
+        lines.push(Line { address, bytes: rom[start..offset].to_vec(), instruction: None });
+    }
+    Disassembly { lines, labels }
+}
+
+/// 跟踪控制流，返回每条可达指令在 rom 中的偏移和所有跳转目标
+fn trace(rom: &[u8], origin: u16, entry: u16) -> (BTreeMap<usize, Instruction>, BTreeSet<u16>) {
+    let mut instructions = BTreeMap::new();
+    let mut labels = BTreeSet::new();
+    // 已经被解码为指令的字节，防止从指令中间开始解码
+    let mut code = vec![false; rom.len()];
+    let word = |offset: usize| (offset + 1 < rom.len()).then(|| u16::from_be_bytes([rom[offset], rom[offset + 1]]));
+    let offset_of = |address: u16| {
+        let offset = address.checked_sub(origin)? as usize;
+        (offset < rom.len()).then_some(offset)
+    };
+
+    let mut pending: Vec<usize> = offset_of(entry).into_iter().collect();
+    while let Some(offset) = pending.pop() {
+        if instructions.contains_key(&offset) {
+            continue;
+        }
+        let Some(opcode) = word(offset) else { continue };
+        let Ok(instruction) = Instruction::decode_long(opcode, word(offset + 2).unwrap_or(0)) else {
+            continue;
+        };
+        let end = offset + instruction.size() as usize;
+        if end > rom.len() || code[offset..end].iter().any(|&c| c) {
+            continue;
+        }
+        instructions.insert(offset, instruction);
+        code[offset..end].fill(true);
+
+        let mut jump = |address: u16, pending: &mut Vec<usize>| {
+            if let Some(target) = offset_of(address) {
+                labels.insert(address);
+                pending.push(target);
+            }
+        };
+        match instruction {
+            Instruction::Ret | Instruction::Exit => {}
+            Instruction::Jp(nnn) => jump(nnn, &mut pending),
+            // 实际目标取决于寄存器，只能跟踪基地址（通常是跳转表的第一项）
+            Instruction::JpV0Addr(nnn) => jump(nnn, &mut pending),
+            Instruction::Call(nnn) => {
+                jump(nnn, &mut pending);
+                pending.push(end);
+            }
+            Instruction::SeVxByte { .. }
+            | Instruction::SneVxByte { .. }
+            | Instruction::SeVxVy { .. }
+            | Instruction::SneVxVy { .. }
+            | Instruction::SkpVx { .. }
+            | Instruction::SknpVx { .. } => {
+                pending.push(end);
+                // 跳过 F000 NNNN 时跳过 4 字节
+                let skipped = if word(end) == Some(0xF000) { 4 } else { 2 };
+                pending.push(end + skipped);
+            }
+            _ => pending.push(end),
+        }
+    }
+    (instructions, labels)
+}
+
+/// 解码内存中 `address` 处的一行，无法解码时按两个字节的数据输出，超出内存时返回 None
+pub fn line_at(memory: &[u8], address: u16) -> Option<Line> {
+    let start = address as usize;
+    let word = |offset: usize| Some(u16::from_be_bytes([*memory.get(offset)?, *memory.get(offset + 1)?]));
+    let opcode = word(start)?;
+    let instruction = Instruction::decode_long(opcode, word(start + 2).unwrap_or(0))
+        .ok()
+        .filter(|instruction| start + instruction.size() as usize <= memory.len());
+    let size = instruction.map_or(2, |instruction| instruction.size() as usize);
+    Some(Line { address, bytes: memory[start..start + size].to_vec(), instruction })
+}
+
+/// 标签名，如 `L2A0`
+pub fn label_name(address: u16) -> String {
+    format!("L{:03X}", address)
+}
+
+/// 每条指令或数据一行，跳转目标前单独一行输出标签
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            if self.labels.contains(&line.address) {
+                writeln!(f, "{}:", label_name(line.address))?;
+            }
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// 格式为 `地址  原始字节  助记符`，数据行的助记符为 `DB`
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex: String = self.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        match self.instruction {
+            Some(instruction) => write!(f, "{:04X}  {:<8}  {}", self.address, hex, instruction),
+            None => {
+                let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{:#04X}", b)).collect();
+                write!(f, "{:04X}  {:<8}  DB {}", self.address, hex, bytes.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(rom: &[u8]) -> Vec<String> {
+        disassemble(rom, 0x200, 0x200).to_string().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn formats_mnemonics() {
+        let cases = [
+            (0x00E0, "CLS"),
+            (0x00EE, "RET"),
+            (0x12A0, "JP 0x2A0"),
+            (0x6310, "LD V3, 0x10"),
+            (0x7101, "ADD V1, 0x01"),
+            (0x8AB6, "SHR VA, VB"),
+            (0xD125, "DRW V1, V2, 5"),
+            (0xF00A, "LD V0, K"),
+            (0xF555, "LD [I], V5"),
+            (0xB300, "JP V0, 0x300"),
+        ];
+        for (opcode, expected) in cases {
+            assert_eq!(Instruction::decode(opcode).unwrap().to_string(), expected);
+        }
+        assert_eq!(Instruction::decode_long(0xF000, 0xE000).unwrap().to_string(), "LD I, LONG 0xE000");
+    }
+
+    #[test]
+    fn data_after_jump_is_not_decoded() {
+        // 1206 00E0 F090 6310 1206
+        let rom = [0x12, 0x06, 0x00, 0xE0, 0xF0, 0x90, 0x63, 0x10, 0x12, 0x06];
+        assert_eq!(
+            text(&rom),
+            [
+                "0200  1206      JP 0x206",
+                "0202  00E0F090  DB 0x00, 0xE0, 0xF0, 0x90",
+                "L206:",
+                "0206  6310      LD V3, 0x10",
+                "0208  1206      JP 0x206",
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_calls_and_skips() {
+        // 220A 3000 F000 0300 00FD / 00EE
+        let rom = [0x22, 0x0A, 0x30, 0x00, 0xF0, 0x00, 0x03, 0x00, 0x00, 0xFD, 0x00, 0xEE];
+        let disassembly = disassemble(&rom, 0x200, 0x200);
+        let code: Vec<_> = disassembly.lines.iter().filter_map(|line| line.instruction).collect();
+        assert_eq!(
+            code,
+            [
+                Instruction::Call(0x20A),
+                Instruction::SeVxByte { x: 0, nn: 0 },
+                Instruction::LdILong(0x0300),
+                Instruction::Exit,
+                Instruction::Ret,
+            ]
+        );
+        assert_eq!(disassembly.labels, BTreeSet::from([0x20A]));
+    }
+
+    #[test]
+    fn traces_from_entry_point_and_origin() {
+        // 0x600：FFFF 1602，从 0x602 开始执行
+        let rom = [0xFF, 0xFF, 0x16, 0x02];
+        let lines: Vec<String> = disassemble(&rom, 0x600, 0x602).to_string().lines().map(str::to_string).collect();
+        assert_eq!(lines, ["0600  FFFF      DB 0xFF, 0xFF", "L602:", "0602  1602      JP 0x602"]);
+        assert!(disassemble(&rom, 0x600, 0x200).lines.iter().all(|line| line.instruction.is_none()));
+    }
+
+    #[test]
+    fn unknown_opcodes_and_odd_tails_are_data() {
+        let rom = [0x00, 0x00, 0x12];
+        assert_eq!(text(&rom), ["0200  000012    DB 0x00, 0x00, 0x12"]);
+    }
+}
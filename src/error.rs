@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// 解释器错误
+///
+/// 客户 rom 中的错误不会让宿主进程崩溃，而是通过这个类型返回给调用方。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chip8Error {
+    /// 无法识别的指令
+    UnknownOpcode(u16),
+    /// 子程序嵌套超过栈深度
+    StackOverflow,
+    /// 栈为空时执行了 RET
+    StackUnderflow,
+    /// rom 超出可用内存
+    RomTooLarge { size: usize, max: usize },
+    /// PC 指向内存之外
+    PcOutOfBounds(u16),
+    /// 字体放置在该地址时超出内存
+    FontOutOfBounds(u16),
+    /// 存档数据损坏或版本不兼容
+    InvalidState(&'static str),
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chip8Error::UnknownOpcode(opcode) => write!(f, "opcode {:#06X} is bad", opcode),
+            Chip8Error::StackOverflow => write!(f, "stack overflow"),
+            Chip8Error::StackUnderflow => write!(f, "return with empty stack"),
+            Chip8Error::RomTooLarge { size, max } => write!(f, "rom is {} bytes, at most {} bytes fit in memory", size, max),
+            Chip8Error::PcOutOfBounds(pc) => write!(f, "program counter {:#06X} is out of memory", pc),
+            Chip8Error::FontOutOfBounds(address) => write!(f, "font at {:#06X} does not fit in memory", address),
+            Chip8Error::InvalidState(reason) => write!(f, "invalid save state: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Chip8Error {}
@@ -0,0 +1,288 @@
+use std::path::Path;
+
+/// ASCII 格式中每种像素值使用的字符：熄灭、第一平面、第二平面、两个平面
+const ASCII_PIXELS: [char; 4] = ['.', '#', '+', '%'];
+
+/// PNG 文件头
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// deflate 未压缩块的最大长度
+const STORED_BLOCK_LENGTH: usize = 0xFFFF;
+
+/// 帧缓冲的导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// 每个像素一个字符，每行一行文本
+    Ascii,
+    /// 文本格式的 PBM（P1）
+    Pbm,
+    /// 二进制格式的 PGM（P5），灰度由调色板的亮度决定
+    Pgm,
+    /// 使用调色板的 PNG
+    Png,
+}
+
+impl Format {
+    /// 根据文件扩展名选择格式，`.pbm`、`.pgm`、`.png` 以外都使用 ASCII
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("pbm") => Format::Pbm,
+            Some("pgm") => Format::Pgm,
+            Some("png") => Format::Png,
+            _ => Format::Ascii,
+        }
+    }
+
+    /// 按格式导出 `screen`，每个像素放大为 `scale * scale` 个像素
+    ///
+    /// ASCII 格式不会放大，PBM 格式不使用调色板。
+    pub fn export(self, screen: &[u8], width: usize, height: usize, scale: usize, palette: &Palette) -> Vec<u8> {
+        match self {
+            Format::Ascii => ascii(screen, width, height).into_bytes(),
+            Format::Pbm => pbm(screen, width, height, scale).into_bytes(),
+            Format::Pgm => pgm(screen, width, height, scale, palette),
+            Format::Png => png(screen, width, height, scale, palette),
+        }
+    }
+}
+
+/// 调色板：熄灭、第一平面、第二平面、两个平面的 RGB 颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette(pub [[u8; 3]; 4]);
+
+impl Palette {
+    /// 解析以逗号分隔的 2 - 4 个十六进制颜色，如 `000000,ffffff` 或 `#000,#fff`
+    ///
+    /// 只给出两个颜色时，第二平面和两个平面都使用前景色；只给出三个颜色时，两个平面使用第二平面的颜色。
+    pub fn parse(text: &str) -> Option<Self> {
+        let colors: Vec<[u8; 3]> = text.split(',').map(|color| parse_color(color.trim())).collect::<Option<_>>()?;
+        let palette = match *colors.as_slice() {
+            [background, foreground] => [background, foreground, foreground, foreground],
+            [background, plane1, plane2] => [background, plane1, plane2, plane2],
+            [background, plane1, plane2, both] => [background, plane1, plane2, both],
+            _ => return None,
+        };
+        Some(Palette(palette))
+    }
+
+    /// 像素值对应的颜色
+    pub fn color(&self, pixel: u8) -> [u8; 3] {
+        self.0[pixel as usize & 0x03]
+    }
+
+    /// 像素值对应的灰度
+    pub fn gray(&self, pixel: u8) -> u8 {
+        let [r, g, b] = self.color(pixel).map(u32::from);
+        ((r * 299 + g * 587 + b * 114) / 1000) as u8
+    }
+}
+
+/// 默认调色板：黑色背景，白色前景，第二平面为浅灰，两个平面为深灰
+impl Default for Palette {
+    fn default() -> Self {
+        Palette([[0x00, 0x00, 0x00], [0xFF, 0xFF, 0xFF], [0xAA, 0xAA, 0xAA], [0x55, 0x55, 0x55]])
+    }
+}
+
+fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    // from_str_radix 接受开头的 +，需要先确认每一位都是十六进制数字
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(digits, 16).ok()?;
+    match digits.len() {
+        // #RGB 中每一位重复一次
+        3 => Some([(value >> 8) as u8 * 0x11, (value >> 4 & 0xF) as u8 * 0x11, (value & 0xF) as u8 * 0x11]),
+        6 => Some([(value >> 16) as u8, (value >> 8) as u8, value as u8]),
+        _ => None,
+    }
+}
+
+/// 将帧缓冲导出为 ASCII 文本
+///
+/// `.` 为熄灭的像素，`#` / `+` / `%` 分别为只点亮第一平面、只点亮第二平面、两个平面都点亮的像素。
+pub fn ascii(screen: &[u8], width: usize, height: usize) -> String {
+    let mut text = String::with_capacity((width + 1) * height);
+    for row in screen.chunks(width).take(height) {
+        text.extend(row.iter().map(|&pixel| ASCII_PIXELS[pixel as usize & 0x03]));
+        text.push('\n');
+    }
+    text
+}
+
+/// 将帧缓冲导出为文本格式的 PBM，任一平面点亮的像素为黑色（1）
+pub fn pbm(screen: &[u8], width: usize, height: usize, scale: usize) -> String {
+    let (pixels, width, height) = scaled(screen, width, height, scale);
+    let mut text = format!("P1\n{} {}\n", width, height);
+    for row in pixels.chunks(width) {
+        let row: Vec<&str> = row.iter().map(|&pixel| if pixel != 0 { "1" } else { "0" }).collect();
+        text.push_str(&row.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+/// 将帧缓冲导出为二进制格式的 PGM
+pub fn pgm(screen: &[u8], width: usize, height: usize, scale: usize, palette: &Palette) -> Vec<u8> {
+    let (pixels, width, height) = scaled(screen, width, height, scale);
+    let mut image = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+    image.extend(pixels.iter().map(|&pixel| palette.gray(pixel)));
+    image
+}
+
+/// 将帧缓冲导出为 8 位索引颜色的 PNG
+///
+/// 图像数据使用未压缩的 deflate 块，不依赖压缩库。
+pub fn png(screen: &[u8], width: usize, height: usize, scale: usize, palette: &Palette) -> Vec<u8> {
+    let (pixels, width, height) = scaled(screen, width, height, scale);
+    // 每行之前是过滤类型，0 表示不过滤
+    let mut raw = Vec::with_capacity((width + 1) * height);
+    for row in pixels.chunks(width) {
+        raw.push(0);
+        raw.extend(row.iter().map(|&pixel| pixel & 0x03));
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 位深 8，索引颜色，deflate 压缩，自适应过滤，不交错
+    header.extend([8, 3, 0, 0, 0]);
+
+    let mut image = PNG_SIGNATURE.to_vec();
+    png_chunk(&mut image, b"IHDR", &header);
+    png_chunk(&mut image, b"PLTE", palette.0.as_flattened());
+    png_chunk(&mut image, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut image, b"IEND", &[]);
+    image
+}
+
+/// 将每个像素放大为 `scale * scale` 个像素，返回像素和放大后的宽高
+fn scaled(screen: &[u8], width: usize, height: usize, scale: usize) -> (Vec<u8>, usize, usize) {
+    let scale = scale.max(1);
+    let mut pixels = Vec::with_capacity(width * height * scale * scale);
+    for row in screen.chunks(width).take(height) {
+        let row: Vec<u8> = row.iter().flat_map(|&pixel| std::iter::repeat_n(pixel, scale)).collect();
+        for _ in 0..scale {
+            pixels.extend_from_slice(&row);
+        }
+    }
+    (pixels, width * scale, height * scale)
+}
+
+fn png_chunk(image: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    image.extend((data.len() as u32).to_be_bytes());
+    let start = image.len();
+    image.extend(kind);
+    image.extend(data);
+    let crc = crc32(&image[start..]);
+    image.extend(crc.to_be_bytes());
+}
+
+/// 使用未压缩块的 zlib 数据流
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // CMF：deflate，32K 窗口；FLG：无字典，使 CMF * 256 + FLG 是 31 的倍数
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(STORED_BLOCK_LENGTH).peekable();
+    if blocks.peek().is_none() {
+        stream.extend([0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        stream.push(last as u8);
+        stream.extend(len.to_le_bytes());
+        stream.extend((!len).to_le_bytes());
+        stream.extend(block);
+    }
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: [u8; 8] = [0, 1, 2, 3, 1, 0, 0, 1];
+
+    #[test]
+    fn exports_ascii() {
+        assert_eq!(ascii(&SCREEN, 4, 2), ".#+%\n#..#\n");
+    }
+
+    #[test]
+    fn exports_pbm() {
+        assert_eq!(pbm(&SCREEN, 4, 2, 1), "P1\n4 2\n0 1 1 1\n1 0 0 1\n");
+        assert_eq!(pbm(&[1, 0], 2, 1, 2), "P1\n4 2\n1 1 0 0\n1 1 0 0\n");
+    }
+
+    #[test]
+    fn exports_pgm_with_palette_brightness() {
+        let image = pgm(&SCREEN, 4, 2, 1, &Palette::default());
+        assert_eq!(&image[..11], b"P5\n4 2\n255\n");
+        assert_eq!(&image[11..], [0x00, 0xFF, 0xAA, 0x55, 0xFF, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn exports_png() {
+        let image = png(&SCREEN, 4, 2, 3, &Palette::default());
+        assert_eq!(image[..8], PNG_SIGNATURE);
+        // IHDR：长度、类型、宽 12、高 6
+        assert_eq!(&image[8..16], b"\0\0\0\x0DIHDR");
+        assert_eq!(&image[16..24], [0, 0, 0, 12, 0, 0, 0, 6]);
+        // IEND 的 CRC 是固定值
+        assert_eq!(&image[image.len() - 12..], b"\0\0\0\0IEND\xAE\x42\x60\x82");
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        let stream = zlib_stored(&[7; STORED_BLOCK_LENGTH + 1]);
+        // 两个块：第一个块不是最后一个，第二个块只有一个字节
+        assert_eq!(stream[2], 0);
+        assert_eq!(&stream[STORED_BLOCK_LENGTH + 7..STORED_BLOCK_LENGTH + 12], [1, 1, 0, 0xFE, 0xFF]);
+    }
+
+    #[test]
+    fn parses_palettes() {
+        assert_eq!(Palette::parse("000000,ffffff"), Some(Palette([[0; 3], [0xFF; 3], [0xFF; 3], [0xFF; 3]])));
+        assert_eq!(
+            Palette::parse("#000, #f80, 112233, 445566"),
+            Some(Palette([[0; 3], [0xFF, 0x88, 0x00], [0x11, 0x22, 0x33], [0x44, 0x55, 0x66]]))
+        );
+        assert_eq!(Palette::parse("000000"), None);
+        assert_eq!(Palette::parse("000000,fffff"), None);
+        assert_eq!(Palette::parse("000000,+fffff"), None);
+        assert_eq!(Palette::parse("+00,fff"), None);
+    }
+
+    #[test]
+    fn chooses_format_by_extension() {
+        assert_eq!(Format::from_path(Path::new("out.PBM")), Format::Pbm);
+        assert_eq!(Format::from_path(Path::new("out.pgm")), Format::Pgm);
+        assert_eq!(Format::from_path(Path::new("out.png")), Format::Png);
+        assert_eq!(Format::from_path(Path::new("out.txt")), Format::Ascii);
+        assert_eq!(Format::from_path(Path::new("out")), Format::Ascii);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::constant::{BIG_FONT_SET, FONT_SET, FONT_SIZE, SCHIP_BIG_FONT_SET, VIP_FONT_SET};
+
+/// 字体
+///
+/// 小字体是 16 个 4 x 5 像素的十六进制数字，供 FX29 使用；
+/// 大字体是 16 个 8 x 10 像素的数字，供 FX30（SUPER-CHIP）使用。
+/// 在内存中小字体之后紧跟大字体。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    pub small: [u8; 80],
+    pub big: [u8; 160],
+}
+
+impl Font {
+    /// COSMAC VIP 的小字体，"1" 和 "7" 的形状与其他平台不同
+    ///
+    /// VIP 没有大字体，大字体使用 Octo 的。
+    pub fn vip() -> Self {
+        Self { small: VIP_FONT_SET, big: BIG_FONT_SET }
+    }
+
+    /// HP48 上的 CHIP-48 的小字体，也是大多数解释器使用的字体
+    ///
+    /// CHIP-48 没有大字体，大字体使用 Octo 的。
+    pub fn chip48() -> Self {
+        Self { small: FONT_SET, big: BIG_FONT_SET }
+    }
+
+    /// SUPER-CHIP 1.1：小字体与 CHIP-48 相同，大字体带有圆角
+    pub fn superchip() -> Self {
+        Self { small: FONT_SET, big: SCHIP_BIG_FONT_SET }
+    }
+
+    /// Octo：小字体与 CHIP-48 相同，大字体是方形的
+    pub fn octo() -> Self {
+        Self { small: FONT_SET, big: BIG_FONT_SET }
+    }
+
+    /// 按名称查找内置字体：`vip`、`chip48`、`schip`、`octo`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "vip" | "cosmac" => Some(Self::vip()),
+            "chip48" => Some(Self::chip48()),
+            "schip" | "superchip" => Some(Self::superchip()),
+            "octo" => Some(Self::octo()),
+            _ => None,
+        }
+    }
+
+    /// 读取自定义字体
+    ///
+    /// 80 字节的数据只包含小字体，大字体使用 Octo 的；240 字节的数据依次包含小字体和大字体。
+    /// 其他长度返回 None。
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut font = Self::default();
+        match data.len() {
+            80 => font.small.copy_from_slice(data),
+            FONT_SIZE => {
+                font.small.copy_from_slice(&data[..80]);
+                font.big.copy_from_slice(&data[80..]);
+            }
+            _ => return None,
+        }
+        Some(font)
+    }
+
+    /// 字体在内存中的内容，长度为 `FONT_SIZE`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.small[..], &self.big[..]].concat()
+    }
+}
+
+/// 默认字体与 Octo 相同
+impl Default for Font {
+    fn default() -> Self {
+        Self::octo()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vip_font_has_distinct_one_and_seven() {
+        let (vip, chip48) = (Font::vip(), Font::chip48());
+        assert_eq!(vip.small[5..10], [0x60, 0x20, 0x20, 0x20, 0x70]);
+        assert_eq!(vip.small[35..40], [0xF0, 0x10, 0x10, 0x10, 0x10]);
+        assert_ne!(vip.small[5..10], chip48.small[5..10]);
+        assert_eq!(vip.small[..5], chip48.small[..5]);
+        assert_eq!(Font::from_name("SCHIP"), Some(Font::superchip()));
+        assert_eq!(Font::from_name("fish"), None);
+    }
+
+    #[test]
+    fn custom_fonts_are_read_from_bytes() {
+        let small: Vec<u8> = (0..80).collect();
+        let font = Font::from_bytes(&small).unwrap();
+        assert_eq!(font.small[..], small[..]);
+        assert_eq!(font.big, BIG_FONT_SET);
+
+        let both: Vec<u8> = (0..FONT_SIZE as u8).collect();
+        let font = Font::from_bytes(&both).unwrap();
+        assert_eq!(font.to_bytes(), both);
+
+        assert_eq!(Font::from_bytes(&[0; 79]), None);
+    }
+}
@@ -0,0 +1,148 @@
+use std::io;
+use std::time::Duration;
+
+use crate::chip8::Chip8;
+use crate::error::Chip8Error;
+use crate::scheduler::Scheduler;
+
+/// 屏幕输出
+pub trait DisplaySink {
+    /// 屏幕内容变化后调用
+    ///
+    /// `screen` 按行存储 `width * height` 个像素，每个像素的取值为 0 - 3，
+    /// bit 0 / bit 1 分别为第一 / 第二平面。
+    fn draw(&mut self, screen: &[u8], width: usize, height: usize) -> io::Result<()>;
+}
+
+/// 按键输入
+pub trait InputSource {
+    /// 每帧执行指令前调用，返回 16 个按键当前是否按下
+    fn keys(&mut self) -> [bool; 16];
+}
+
+/// 声音输出
+pub trait AudioSink {
+    /// 每帧执行指令后调用
+    ///
+    /// 声音定时器非零时应当发声（`Chip8::is_sound_active`），
+    /// XO-CHIP 程序的音频模式和音高也可以从 `chip8` 读取。
+    fn update(&mut self, chip8: &Chip8) -> io::Result<()>;
+}
+
+/// 什么也不做的前端，用于无界面运行和测试：不显示、没有按键、不发声
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullBackend;
+
+impl DisplaySink for NullBackend {
+    fn draw(&mut self, _screen: &[u8], _width: usize, _height: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl InputSource for NullBackend {
+    fn keys(&mut self) -> [bool; 16] {
+        [false; 16]
+    }
+}
+
+impl AudioSink for NullBackend {
+    fn update(&mut self, _chip8: &Chip8) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 运行一帧：从 `input` 读取按键，然后按经过的实际时间执行指令并递减定时器
+///
+/// 返回执行的指令数。暂停时不调用，输出由 `present` 负责。
+pub fn run_frame(
+    chip8: &mut Chip8,
+    scheduler: &mut Scheduler,
+    elapsed: Duration,
+    input: &mut dyn InputSource,
+) -> Result<u32, Chip8Error> {
+    chip8.set_keys(input.keys());
+    scheduler.update(chip8, elapsed)
+}
+
+/// 输出一帧：屏幕发生变化时交给 `display` 绘制，然后更新 `audio`
+pub fn present(chip8: &mut Chip8, display: &mut dyn DisplaySink, audio: &mut dyn AudioSink) -> io::Result<()> {
+    if chip8.take_display_dirty() {
+        display.draw(chip8.screen(), chip8.screen_width(), chip8.screen_height())?;
+    }
+    audio.update(chip8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录收到的内容
+    #[derive(Default)]
+    struct Recorder {
+        frames: Vec<Vec<u8>>,
+        sound: Vec<bool>,
+        keys: [bool; 16],
+    }
+
+    impl DisplaySink for Recorder {
+        fn draw(&mut self, screen: &[u8], _width: usize, _height: usize) -> io::Result<()> {
+            self.frames.push(screen.to_vec());
+            Ok(())
+        }
+    }
+
+    impl InputSource for Recorder {
+        fn keys(&mut self) -> [bool; 16] {
+            self.keys
+        }
+    }
+
+    impl AudioSink for Recorder {
+        fn update(&mut self, chip8: &Chip8) -> io::Result<()> {
+            self.sound.push(chip8.is_sound_active());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn frame_reads_input_and_presents_changes() {
+        // 6000 F029 D005 6002 F018 E09E 120A 00E0 1210：
+        // 画出 "0" 并设置声音定时器，按住 2 时跳出循环并清屏
+        let mut chip8 = Chip8::new();
+        let rom = [0x60, 0x00, 0xF0, 0x29, 0xD0, 0x05, 0x60, 0x02, 0xF0, 0x18, 0xE0, 0x9E, 0x12, 0x0A, 0x00, 0xE0, 0x12, 0x10];
+        chip8.load_rom(&rom).unwrap();
+        let mut scheduler = Scheduler::new();
+        let mut recorder = Recorder::default();
+
+        let mut audio = NullBackend;
+        run_frame(&mut chip8, &mut scheduler, Scheduler::frame_duration(), &mut recorder).unwrap();
+        present(&mut chip8, &mut recorder, &mut audio).unwrap();
+        assert_eq!(recorder.frames.len(), 1);
+        assert!(recorder.frames[0].iter().any(|&pixel| pixel != 0));
+
+        // 屏幕没有变化时不重绘
+        let mut display = NullBackend;
+        present(&mut chip8, &mut display, &mut recorder).unwrap();
+        present(&mut chip8, &mut recorder, &mut audio).unwrap();
+        assert_eq!(recorder.frames.len(), 1);
+        assert_eq!(recorder.sound, [true]);
+
+        // 清屏之后重绘
+        recorder.keys[2] = true;
+        run_frame(&mut chip8, &mut scheduler, Scheduler::frame_duration(), &mut recorder).unwrap();
+        assert!(chip8.is_key_pressed(2));
+        present(&mut chip8, &mut recorder, &mut audio).unwrap();
+        assert_eq!(recorder.frames.len(), 2);
+        assert!(recorder.frames[1].iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn null_backend_has_no_keys() {
+        let mut chip8 = Chip8::new();
+        chip8.press_key(5);
+        // 1200
+        chip8.load_rom(&[0x12, 0x00]).unwrap();
+        run_frame(&mut chip8, &mut Scheduler::new(), Scheduler::frame_duration(), &mut NullBackend).unwrap();
+        assert!(!chip8.is_key_pressed(5));
+    }
+}
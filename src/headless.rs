@@ -0,0 +1,166 @@
+use std::error::Error;
+use std::fmt::Write as _;
+
+use crate::chip8::Chip8;
+use crate::constant::TIMER_FREQUENCY;
+use crate::frontend::AudioSink;
+use crate::instruction::Instruction;
+use crate::trace::Tracer;
+
+/// 无界面运行停止的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Halt {
+    /// 执行完指定的周期数
+    Cycles,
+    /// 1NNN 跳转到自身，之后不会再有任何变化
+    Loop(u16),
+    /// 程序执行了 00FD
+    Exited,
+}
+
+impl Halt {
+    fn name(&self) -> &'static str {
+        match self {
+            Halt::Cycles => "cycles",
+            Halt::Loop(_) => "loop",
+            Halt::Exited => "exit",
+        }
+    }
+}
+
+/// 无界面运行的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub halt: Halt,
+    /// 执行的指令数
+    pub cycles: u64,
+}
+
+/// 不使用任何界面运行最多 `cycles` 条指令，跳转到自身或退出时提前停止
+///
+/// 每执行 `clock_rate / 60` 条指令递减一次定时器并更新 `audio`，因此结果与实际运行相同，但不受时间影响。
+/// 没有键盘输入，等待按键的程序会一直等待到周期数用完。
+/// 跳转到自身时补完当前帧，并继续递减定时器直到声音定时器归零，使最后的声音完整播放。
+pub fn run(
+    chip8: &mut Chip8,
+    cycles: u64,
+    clock_rate: u32,
+    mut tracer: Option<&mut Tracer>,
+    audio: &mut dyn AudioSink,
+) -> Result<Outcome, Box<dyn Error>> {
+    let cycles_per_frame = (clock_rate / TIMER_FREQUENCY).max(1) as u64;
+    let mut executed = 0;
+    while executed < cycles {
+        if chip8.has_exited() {
+            return Ok(Outcome { halt: Halt::Exited, cycles: executed });
+        }
+        let info = match tracer.as_deref_mut() {
+            Some(tracer) => tracer.step(chip8)?,
+            None => chip8.step()?,
+        };
+        executed += 1;
+        // 不使用 is_multiple_of，它需要 Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        let frame_end = executed % cycles_per_frame == 0;
+        if frame_end {
+            chip8.tick_timers();
+            audio.update(chip8)?;
+        }
+        if info.instruction == Instruction::Jp(info.pc) {
+            // 之后只会重复跳转：补完当前帧，再继续递减定时器直到声音停止
+            if !frame_end {
+                chip8.tick_timers();
+                audio.update(chip8)?;
+            }
+            while chip8.sound_timer() > 0 {
+                chip8.tick_timers();
+                audio.update(chip8)?;
+            }
+            return Ok(Outcome { halt: Halt::Loop(info.pc), cycles: executed });
+        }
+    }
+    let halt = if chip8.has_exited() { Halt::Exited } else { Halt::Cycles };
+    Ok(Outcome { halt, cycles: executed })
+}
+
+/// 将寄存器状态导出为 JSON
+pub fn registers_json(chip8: &Chip8, outcome: &Outcome) -> String {
+    let list = |values: Vec<String>| values.join(", ");
+    let mut json = String::from("{\n");
+    let _ = writeln!(json, "  \"halt\": \"{}\",", outcome.halt.name());
+    let _ = writeln!(json, "  \"cycles\": {},", outcome.cycles);
+    let _ = writeln!(json, "  \"pc\": {},", chip8.program_counter());
+    let _ = writeln!(json, "  \"i\": {},", chip8.address_register());
+    let _ = writeln!(json, "  \"v\": [{}],", list(chip8.registers().iter().map(u8::to_string).collect()));
+    let _ = writeln!(json, "  \"stack\": [{}],", list(chip8.stack().iter().map(u16::to_string).collect()));
+    let _ = writeln!(json, "  \"delay_timer\": {},", chip8.delay_timer());
+    let _ = writeln!(json, "  \"sound_timer\": {}", chip8.sound_timer());
+    json.push_str("}\n");
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::NullBackend;
+
+    fn boot(rom: &[u8]) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(rom).unwrap();
+        chip8
+    }
+
+    #[test]
+    fn stops_at_self_jump() {
+        // 6005 7001 1204
+        let mut chip8 = boot(&[0x60, 0x05, 0x70, 0x01, 0x12, 0x04]);
+        let outcome = run(&mut chip8, 1000, 600, None, &mut NullBackend).unwrap();
+        assert_eq!(outcome, Outcome { halt: Halt::Loop(0x204), cycles: 3 });
+        assert_eq!(chip8.v(0), 6);
+    }
+
+    #[test]
+    fn self_jump_finishes_frame_and_drains_sound_timer() {
+        struct Recorder(Vec<u8>);
+        impl AudioSink for Recorder {
+            fn update(&mut self, chip8: &Chip8) -> std::io::Result<()> {
+                self.0.push(chip8.sound_timer());
+                Ok(())
+            }
+        }
+        // 6003 F018 1204
+        let mut chip8 = boot(&[0x60, 0x03, 0xF0, 0x18, 0x12, 0x04]);
+        let mut audio = Recorder(Vec::new());
+        let outcome = run(&mut chip8, 1000, 600, None, &mut audio).unwrap();
+        assert_eq!(outcome, Outcome { halt: Halt::Loop(0x204), cycles: 3 });
+        assert_eq!(audio.0, [2, 1, 0]);
+    }
+
+    #[test]
+    fn stops_after_cycle_count_and_ticks_timers() {
+        // 60FF F015 7101 1204
+        let mut chip8 = boot(&[0x60, 0xFF, 0xF0, 0x15, 0x71, 0x01, 0x12, 0x04]);
+        let outcome = run(&mut chip8, 102, 600, None, &mut NullBackend).unwrap();
+        assert_eq!(outcome, Outcome { halt: Halt::Cycles, cycles: 102 });
+        assert_eq!(chip8.delay_timer(), 0xFF - 10);
+    }
+
+    #[test]
+    fn stops_on_exit() {
+        let mut chip8 = boot(&[0x00, 0xFD]);
+        assert_eq!(run(&mut chip8, 10, 600, None, &mut NullBackend).unwrap(), Outcome { halt: Halt::Exited, cycles: 1 });
+    }
+
+    #[test]
+    fn exports_registers_as_json() {
+        let mut chip8 = boot(&[0x6A, 0x2A, 0x22, 0x06, 0x00, 0x00, 0x12, 0x06]);
+        let outcome = run(&mut chip8, 100, 600, None, &mut NullBackend).unwrap();
+        let json = registers_json(&chip8, &outcome);
+        assert_eq!(
+            json,
+            "{\n  \"halt\": \"loop\",\n  \"cycles\": 3,\n  \"pc\": 518,\n  \"i\": 0,\n  \
+             \"v\": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0, 0],\n  \"stack\": [516],\n  \
+             \"delay_timer\": 0,\n  \"sound_timer\": 0\n}\n"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt;
+
+use crate::error::Chip8Error;
+
+/// 解码后的指令
+///
+/// 每个变体对应 `Instructions` 中的一个方法，字段为从操作码中取出的操作数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// 00E0
+    Cls,
+    /// 00EE
+    Ret,
+    /// 00CN（SUPER-CHIP）
+    Scd(u8),
+    /// 00FB（SUPER-CHIP）
+    Scr,
+    /// 00FC（SUPER-CHIP）
+    Scl,
+    /// 00FD（SUPER-CHIP）
+    Exit,
+    /// 00FE（SUPER-CHIP）
+    Low,
+    /// 00FF（SUPER-CHIP）
+    High,
+    /// 1NNN
+    Jp(u16),
+    /// 2NNN
+    Call(u16),
+    /// 3XNN
+    SeVxByte { x: usize, nn: u8 },
+    /// 4XNN
+    SneVxByte { x: usize, nn: u8 },
+    /// 5XY0
+    SeVxVy { x: usize, y: usize },
+    /// 5XY2（XO-CHIP）
+    SaveVxVy { x: usize, y: usize },
+    /// 5XY3（XO-CHIP）
+    LoadVxVy { x: usize, y: usize },
+    /// 6XNN
+    LdVxByte { x: usize, nn: u8 },
+    /// 7XNN
+    AddVxByte { x: usize, nn: u8 },
+    /// 8XY0
+    LdVxVy { x: usize, y: usize },
+    /// 8XY1
+    OrVxVy { x: usize, y: usize },
+    /// 8XY2
+    AndVxVy { x: usize, y: usize },
+    /// 8XY3
+    XorVxVy { x: usize, y: usize },
+    /// 8XY4
+    AddVxVy { x: usize, y: usize },
+    /// 8XY5
+    SubVxVy { x: usize, y: usize },
+    /// 8XY6
+    ShrVxVy { x: usize, y: usize },
+    /// 8XY7
+    SubnVxVy { x: usize, y: usize },
+    /// 8XYE
+    ShlVxVy { x: usize, y: usize },
+    /// 9XY0
+    SneVxVy { x: usize, y: usize },
+    /// ANNN
+    LdIAddr(u16),
+    /// BNNN
+    JpV0Addr(u16),
+    /// CXNN
+    RndVxByte { x: usize, nn: u8 },
+    /// DXYN，N 为 0 时绘制 16 x 16 的精灵（SUPER-CHIP）
+    DrwVxVyNibble { x: usize, y: usize, n: u8 },
+    /// EX9E
+    SkpVx { x: usize },
+    /// EXA1
+    SknpVx { x: usize },
+    /// F000 NNNN（XO-CHIP），长度为 4 字节
+    LdILong(u16),
+    /// FN01（XO-CHIP）
+    Plane(u8),
+    /// F002（XO-CHIP）
+    Audio,
+    /// FX07
+    LdVxDt { x: usize },
+    /// FX0A
+    LdVxK { x: usize },
+    /// FX15
+    LdDtVx { x: usize },
+    /// FX18
+    LdStVx { x: usize },
+    /// FX1E
+    AddIVx { x: usize },
+    /// FX29
+    LdFVx { x: usize },
+    /// FX30（SUPER-CHIP）
+    LdHfVx { x: usize },
+    /// FX33
+    LdBVx { x: usize },
+    /// FX3A（XO-CHIP）
+    PitchVx { x: usize },
+    /// FX55
+    LdIVx { x: usize },
+    /// FX65
+    LdVxI { x: usize },
+    /// FX75（SUPER-CHIP）
+    LdRVx { x: usize },
+    /// FX85（SUPER-CHIP）
+    LdVxR { x: usize },
+}
+
+impl Instruction {
+    /// 将两字节的操作码解码为指令
+    ///
+    /// F000 NNNN 需要读取后两个字节，应使用 `decode_long`。
+    pub fn decode(opcode: u16) -> Result<Self, Chip8Error> {
+        let x = ((opcode & 0x0F00) >> 8) as usize;
+        let y = ((opcode & 0x00F0) >> 4) as usize;
+        let n = (opcode & 0x000F) as u8;
+        let nn = (opcode & 0x00FF) as u8;
+        let nnn = opcode & 0x0FFF;
+
+        let instruction = match opcode & 0xF000 {
+            0x0000 => match opcode {
+                0x00E0 => Instruction::Cls,
+                0x00EE => Instruction::Ret,
+                0x00C0..=0x00CF => Instruction::Scd(n),
+                0x00FB => Instruction::Scr,
+                0x00FC => Instruction::Scl,
+                0x00FD => Instruction::Exit,
+                0x00FE => Instruction::Low,
+                0x00FF => Instruction::High,
+                _ => return Err(Chip8Error::UnknownOpcode(opcode)),
+            },
+            0x1000 => Instruction::Jp(nnn),
+            0x2000 => Instruction::Call(nnn),
+            0x3000 => Instruction::SeVxByte { x, nn },
+            0x4000 => Instruction::SneVxByte { x, nn },
+            0x5000 => match n {
+                0x0 => Instruction::SeVxVy { x, y },
+                0x2 => Instruction::SaveVxVy { x, y },
+                0x3 => Instruction::LoadVxVy { x, y },
+                _ => return Err(Chip8Error::UnknownOpcode(opcode)),
+            },
+            0x6000 => Instruction::LdVxByte { x, nn },
+            0x7000 => Instruction::AddVxByte { x, nn },
+            0x8000 => match n {
+                0x0 => Instruction::LdVxVy { x, y },
+                0x1 => Instruction::OrVxVy { x, y },
+                0x2 => Instruction::AndVxVy { x, y },
+                0x3 => Instruction::XorVxVy { x, y },
+                0x4 => Instruction::AddVxVy { x, y },
+                0x5 => Instruction::SubVxVy { x, y },
+                0x6 => Instruction::ShrVxVy { x, y },
+                0x7 => Instruction::SubnVxVy { x, y },
+                0xE => Instruction::ShlVxVy { x, y },
+                _ => return Err(Chip8Error::UnknownOpcode(opcode)),
+            },
+            0x9000 if n == 0 => Instruction::SneVxVy { x, y },
+            0xA000 => Instruction::LdIAddr(nnn),
+            0xB000 => Instruction::JpV0Addr(nnn),
+            0xC000 => Instruction::RndVxByte { x, nn },
+            0xD000 => Instruction::DrwVxVyNibble { x, y, n },
+            0xE000 => match nn {
+                0x9E => Instruction::SkpVx { x },
+                0xA1 => Instruction::SknpVx { x },
+                _ => return Err(Chip8Error::UnknownOpcode(opcode)),
+            },
+            0xF000 => match nn {
+                0x01 => Instruction::Plane(x as u8),
+                0x02 if x == 0 => Instruction::Audio,
+                0x07 => Instruction::LdVxDt { x },
+                0x0A => Instruction::LdVxK { x },
+                0x15 => Instruction::LdDtVx { x },
+                0x18 => Instruction::LdStVx { x },
+                0x1E => Instruction::AddIVx { x },
+                0x29 => Instruction::LdFVx { x },
+                0x30 => Instruction::LdHfVx { x },
+                0x33 => Instruction::LdBVx { x },
+                0x3A => Instruction::PitchVx { x },
+                0x55 => Instruction::LdIVx { x },
+                0x65 => Instruction::LdVxI { x },
+                0x75 => Instruction::LdRVx { x },
+                0x85 => Instruction::LdVxR { x },
+                _ => return Err(Chip8Error::UnknownOpcode(opcode)),
+            },
+            _ => return Err(Chip8Error::UnknownOpcode(opcode)),
+        };
+        Ok(instruction)
+    }
+
+    /// 解码可能占用 4 字节的指令，`next` 为操作码之后的两个字节
+    pub fn decode_long(opcode: u16, next: u16) -> Result<Self, Chip8Error> {
+        if opcode == 0xF000 {
+            return Ok(Instruction::LdILong(next));
+        }
+        Self::decode(opcode)
+    }
+
+    /// 将指令编码为字节，与 `decode` / `decode_long` 互逆
+    pub fn encode(&self) -> Vec<u8> {
+        let xy = |high: u16, x: usize, y: usize, low: u16| high | (x as u16) << 8 | (y as u16) << 4 | low;
+        let xnn = |high: u16, x: usize, nn: u8| high | (x as u16) << 8 | nn as u16;
+        let opcode = match *self {
+            Instruction::Cls => 0x00E0,
+            Instruction::Ret => 0x00EE,
+            Instruction::Scd(n) => 0x00C0 | n as u16,
+            Instruction::Scr => 0x00FB,
+            Instruction::Scl => 0x00FC,
+            Instruction::Exit => 0x00FD,
+            Instruction::Low => 0x00FE,
+            Instruction::High => 0x00FF,
+            Instruction::Jp(nnn) => 0x1000 | nnn,
+            Instruction::Call(nnn) => 0x2000 | nnn,
+            Instruction::SeVxByte { x, nn } => xnn(0x3000, x, nn),
+            Instruction::SneVxByte { x, nn } => xnn(0x4000, x, nn),
+            Instruction::SeVxVy { x, y } => xy(0x5000, x, y, 0x0),
+            Instruction::SaveVxVy { x, y } => xy(0x5000, x, y, 0x2),
+            Instruction::LoadVxVy { x, y } => xy(0x5000, x, y, 0x3),
+            Instruction::LdVxByte { x, nn } => xnn(0x6000, x, nn),
+            Instruction::AddVxByte { x, nn } => xnn(0x7000, x, nn),
+            Instruction::LdVxVy { x, y } => xy(0x8000, x, y, 0x0),
+            Instruction::OrVxVy { x, y } => xy(0x8000, x, y, 0x1),
+            Instruction::AndVxVy { x, y } => xy(0x8000, x, y, 0x2),
+            Instruction::XorVxVy { x, y } => xy(0x8000, x, y, 0x3),
+            Instruction::AddVxVy { x, y } => xy(0x8000, x, y, 0x4),
+            Instruction::SubVxVy { x, y } => xy(0x8000, x, y, 0x5),
+            Instruction::ShrVxVy { x, y } => xy(0x8000, x, y, 0x6),
+            Instruction::SubnVxVy { x, y } => xy(0x8000, x, y, 0x7),
+            Instruction::ShlVxVy { x, y } => xy(0x8000, x, y, 0xE),
+            Instruction::SneVxVy { x, y } => xy(0x9000, x, y, 0x0),
+            Instruction::LdIAddr(nnn) => 0xA000 | nnn,
+            Instruction::JpV0Addr(nnn) => 0xB000 | nnn,
+            Instruction::RndVxByte { x, nn } => xnn(0xC000, x, nn),
+            Instruction::DrwVxVyNibble { x, y, n } => xy(0xD000, x, y, n as u16),
+            Instruction::SkpVx { x } => xnn(0xE000, x, 0x9E),
+            Instruction::SknpVx { x } => xnn(0xE000, x, 0xA1),
+            Instruction::LdILong(nnnn) => return [0xF0, 0x00, (nnnn >> 8) as u8, nnnn as u8].to_vec(),
+            Instruction::Plane(n) => xnn(0xF000, n as usize, 0x01),
+            Instruction::Audio => 0xF002,
+            Instruction::LdVxDt { x } => xnn(0xF000, x, 0x07),
+            Instruction::LdVxK { x } => xnn(0xF000, x, 0x0A),
+            Instruction::LdDtVx { x } => xnn(0xF000, x, 0x15),
+            Instruction::LdStVx { x } => xnn(0xF000, x, 0x18),
+            Instruction::AddIVx { x } => xnn(0xF000, x, 0x1E),
+            Instruction::LdFVx { x } => xnn(0xF000, x, 0x29),
+            Instruction::LdHfVx { x } => xnn(0xF000, x, 0x30),
+            Instruction::LdBVx { x } => xnn(0xF000, x, 0x33),
+            Instruction::PitchVx { x } => xnn(0xF000, x, 0x3A),
+            Instruction::LdIVx { x } => xnn(0xF000, x, 0x55),
+            Instruction::LdVxI { x } => xnn(0xF000, x, 0x65),
+            Instruction::LdRVx { x } => xnn(0xF000, x, 0x75),
+            Instruction::LdVxR { x } => xnn(0xF000, x, 0x85),
+        };
+        opcode.to_be_bytes().to_vec()
+    }
+
+    /// 指令占用的字节数
+    pub fn size(&self) -> u16 {
+        match self {
+            Instruction::LdILong(_) => 4,
+            _ => 2,
+        }
+    }
+
+    /// 是否为 XO-CHIP 扩展指令：F000 NNNN、5XY2、5XY3、FN01、F002、FX3A
+    pub fn is_xochip(&self) -> bool {
+        matches!(
+            self,
+            Instruction::LdILong(_)
+                | Instruction::SaveVxVy { .. }
+                | Instruction::LoadVxVy { .. }
+                | Instruction::Plane(_)
+                | Instruction::Audio
+                | Instruction::PitchVx { .. }
+        )
+    }
+}
+
+/// 以助记符显示指令，如 `JP 0x2A0`、`LD V3, 0x10`
+///
+/// 地址显示为三位十六进制数，字节显示为两位十六进制数，半字节显示为十进制数。
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::Scd(n) => write!(f, "SCD {}", n),
+            Instruction::Scr => write!(f, "SCR"),
+            Instruction::Scl => write!(f, "SCL"),
+            Instruction::Exit => write!(f, "EXIT"),
+            Instruction::Low => write!(f, "LOW"),
+            Instruction::High => write!(f, "HIGH"),
+            Instruction::Jp(nnn) => write!(f, "JP {:#05X}", nnn),
+            Instruction::Call(nnn) => write!(f, "CALL {:#05X}", nnn),
+            Instruction::SeVxByte { x, nn } => write!(f, "SE V{:X}, {:#04X}", x, nn),
+            Instruction::SneVxByte { x, nn } => write!(f, "SNE V{:X}, {:#04X}", x, nn),
+            Instruction::SeVxVy { x, y } => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::SaveVxVy { x, y } => write!(f, "SAVE V{:X}, V{:X}", x, y),
+            Instruction::LoadVxVy { x, y } => write!(f, "LOAD V{:X}, V{:X}", x, y),
+            Instruction::LdVxByte { x, nn } => write!(f, "LD V{:X}, {:#04X}", x, nn),
+            Instruction::AddVxByte { x, nn } => write!(f, "ADD V{:X}, {:#04X}", x, nn),
+            Instruction::LdVxVy { x, y } => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::OrVxVy { x, y } => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::AndVxVy { x, y } => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::XorVxVy { x, y } => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::AddVxVy { x, y } => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::SubVxVy { x, y } => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::ShrVxVy { x, y } => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::SubnVxVy { x, y } => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::ShlVxVy { x, y } => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::SneVxVy { x, y } => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Instruction::LdIAddr(nnn) => write!(f, "LD I, {:#05X}", nnn),
+            Instruction::JpV0Addr(nnn) => write!(f, "JP V0, {:#05X}", nnn),
+            Instruction::RndVxByte { x, nn } => write!(f, "RND V{:X}, {:#04X}", x, nn),
+            Instruction::DrwVxVyNibble { x, y, n } => write!(f, "DRW V{:X}, V{:X}, {}", x, y, n),
+            Instruction::SkpVx { x } => write!(f, "SKP V{:X}", x),
+            Instruction::SknpVx { x } => write!(f, "SKNP V{:X}", x),
+            Instruction::LdILong(nnnn) => write!(f, "LD I, LONG {:#06X}", nnnn),
+            Instruction::Plane(n) => write!(f, "PLANE {}", n),
+            Instruction::Audio => write!(f, "AUDIO"),
+            Instruction::LdVxDt { x } => write!(f, "LD V{:X}, DT", x),
+            Instruction::LdVxK { x } => write!(f, "LD V{:X}, K", x),
+            Instruction::LdDtVx { x } => write!(f, "LD DT, V{:X}", x),
+            Instruction::LdStVx { x } => write!(f, "LD ST, V{:X}", x),
+            Instruction::AddIVx { x } => write!(f, "ADD I, V{:X}", x),
+            Instruction::LdFVx { x } => write!(f, "LD F, V{:X}", x),
+            Instruction::LdHfVx { x } => write!(f, "LD HF, V{:X}", x),
+            Instruction::LdBVx { x } => write!(f, "LD B, V{:X}", x),
+            Instruction::PitchVx { x } => write!(f, "PITCH V{:X}", x),
+            Instruction::LdIVx { x } => write!(f, "LD [I], V{:X}", x),
+            Instruction::LdVxI { x } => write!(f, "LD V{:X}, [I]", x),
+            Instruction::LdRVx { x } => write!(f, "LD R, V{:X}", x),
+            Instruction::LdVxR { x } => write!(f, "LD V{:X}, R", x),
+        }
+    }
+}
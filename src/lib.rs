@@ -0,0 +1,44 @@
+//! CHIP-8 / SUPER-CHIP / XO-CHIP 解释器
+//!
+//! [`Chip8`] 是解释器本身，不依赖任何界面：由调用方决定何时执行指令（[`Chip8::step`]）、
+//! 何时以 60Hz 递减定时器（[`Chip8::tick_timers`]）以及如何显示屏幕和输入按键。
+//! 寄存器、内存、PC、栈、定时器和屏幕都可以通过 `Chip8` 的方法读取和修改，
+//! 每条指令也可以通过 [`Instructions`] 直接执行。
+//!
+//! ```
+//! use chip8_rs::{Chip8, Quirks};
+//!
+//! let mut chip8 = Chip8::new_with(Quirks::cosmac_vip());
+//! // 6A2A 1202：VA = 0x2A，然后跳转到自身
+//! chip8.load_rom(&[0x6A, 0x2A, 0x12, 0x02]).unwrap();
+//! chip8.step().unwrap();
+//! assert_eq!(chip8.v(0xA), 0x2A);
+//! assert_eq!(chip8.program_counter(), 0x202);
+//! ```
+//!
+//! 其余模块是建立在解释器之上的工具：按真实时间驱动解释器的 [`scheduler`]、
+//! 屏幕、按键和声音后端的接口 [`frontend`]、无界面运行的 [`headless`]、生成蜂鸣声的 [`audio`]、
+//! 倒带、汇编器、反汇编器、调试器、执行跟踪和屏幕导出。
+
+pub mod asm;
+pub mod audio;
+pub mod chip8;
+pub mod constant;
+pub mod debugger;
+pub mod disasm;
+pub mod error;
+pub mod export;
+pub mod font;
+pub mod frontend;
+pub mod headless;
+pub mod instruction;
+pub mod quirks;
+pub mod rewind;
+pub mod scheduler;
+mod state;
+pub mod trace;
+
+pub use crate::chip8::{Chip8, Instructions, StepInfo};
+pub use crate::error::Chip8Error;
+pub use crate::instruction::Instruction;
+pub use crate::quirks::Quirks;
@@ -1,6 +1,435 @@
-mod chip8;
-mod constant;
+mod terminal;
 
-fn main() {
-    println!("Hello, world!");
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use chip8_rs::constant::{DEFAULT_CLOCK_RATE, FONT_ADDRESS, PROGRAM_START};
+use chip8_rs::audio::{Tone, WavRecorder, Waveform};
+use chip8_rs::debugger::{self, Debugger};
+use chip8_rs::export::{Format, Palette};
+use chip8_rs::font::Font;
+use chip8_rs::frontend::{AudioSink, NullBackend};
+use chip8_rs::rewind::Rewind;
+use chip8_rs::scheduler::Scheduler;
+use chip8_rs::trace::Tracer;
+use chip8_rs::{asm, disasm, frontend, headless, Chip8, Quirks};
+
+use crate::terminal::{Bell, Command, Terminal};
+
+const USAGE: &str = "usage: chip8-rs [run] [--speed HZ] [--quirks PROFILE] [--trace FILE] <rom>
+       chip8-rs run --headless [--cycles N] [--dump-screen FILE] [--dump-registers FILE] [--audio FILE] <rom>
+       chip8-rs debug [--speed HZ] [--quirks PROFILE] <rom>
+       chip8-rs disasm [--load-address ADDR] [--entry ADDR] <rom>
+       chip8-rs asm [--load-address ADDR] <source> [-o <rom>]
+
+  --speed HZ            instructions per second (default 600)
+  --quirks PROFILE      modern, vip, chip48, schip or xochip (default: modern)
+  --load-address ADDR   hex address the rom is loaded at (default 200),
+                        or eti660 for 600
+  --entry ADDR          hex address execution starts at (default: load address),
+                        e.g. 2C0 or 260 for CHIP-8 HIRES roms
+  --font NAME|FILE      vip, chip48, schip or octo (default), or a file with
+                        80 bytes (small font) or 240 bytes (small + big font)
+  --font-address ADDR   hex address the font is stored at (default 0)
+  --trace FILE          log every executed instruction to FILE
+  --scale N             pixel size of screenshots and screen dumps
+  --palette COLORS      2 - 4 comma separated hex colors for images,
+                        e.g. 000000,ffffff or 000000,ffffff,aaaaaa,555555
+
+headless:
+  --headless              run without a terminal until N cycles have run,
+                          the program jumps to itself or exits
+  --cycles N              instructions to run (default 1000000)
+  --dump-screen FILE      write the screen to FILE (.pbm, .pgm, .png or ASCII)
+  --dump-registers FILE   write the registers as JSON to FILE (default: stdout)
+  --audio FILE            record the sound to a WAV file
+  --tone HZ               beep frequency (default 440)
+  --waveform NAME         square or sine (default square)
+
+keys:
+  1 2 3 4 / Q W E R / A S D F / Z X C V   CHIP-8 keypad
+  P        pause / resume
+  F5       reset
+  F2 / F3  save / load state (<rom>.state)
+  F12      save a screenshot (<rom>.png)
+  Left     rewind (hold to keep rewinding)
+  + / -    faster / slower
+  Esc      quit";
+
+/// 无界面运行时默认执行的指令数
+const HEADLESS_CYCLES: u64 = 1_000_000;
+
+/// 截图默认的像素大小
+const SCREENSHOT_SCALE: usize = 8;
+
+/// 倒带快照的间隔（帧）和数量：每秒 10 个快照，最多回退 60 秒
+const REWIND_INTERVAL: u32 = 6;
+const REWIND_DEPTH: usize = 600;
+
+/// 子命令
+enum Subcommand {
+    /// 在终端中运行 rom
+    Run(Options),
+    /// 在命令行调试器中运行 rom
+    Debug(Options),
+    /// 反汇编 rom 并输出到标准输出
+    Disasm { rom: String, load_address: u16, entry_point: u16 },
+    /// 汇编源文件，未指定输出文件时将扩展名替换为 .ch8
+    Asm { source: String, output: Option<String>, load_address: u16 },
+}
+
+/// 命令行参数
+struct Options {
+    rom: String,
+    speed: u32,
+    quirks: Quirks,
+    // rom 的加载地址和入口地址
+    load_address: u16,
+    entry_point: u16,
+    // 字体和字体的起始地址
+    font: Font,
+    font_address: u16,
+    // 执行跟踪的输出文件
+    trace: Option<String>,
+    // 不使用终端运行
+    headless: bool,
+    // 无界面运行的指令数
+    cycles: u64,
+    // 无界面运行结束后导出屏幕的文件
+    dump_screen: Option<String>,
+    // 无界面运行结束后导出寄存器的文件
+    dump_registers: Option<String>,
+    // 无界面运行时录制声音的 WAV 文件
+    audio: Option<String>,
+    tone: Tone,
+    // 截图和导出屏幕的像素大小
+    scale: Option<usize>,
+    palette: Palette,
+}
+
+fn parse_command(args: impl Iterator<Item = String>) -> Result<Subcommand, String> {
+    let mut args = args.peekable();
+    match args.peek().map(String::as_str) {
+        Some("run") => {
+            args.next();
+            parse_args(args).map(Subcommand::Run)
+        }
+        Some("debug") => {
+            args.next();
+            parse_args(args).map(Subcommand::Debug)
+        }
+        Some("disasm") => {
+            args.next();
+            let mut rom = None;
+            let mut load_address = PROGRAM_START;
+            let mut entry_point = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--load-address" => load_address = parse_address(args.next(), "--load-address")?,
+                    "--entry" => entry_point = Some(parse_address(args.next(), "--entry")?),
+                    _ if arg.starts_with('-') => return Err(format!("unknown option: {}\n\n{}", arg, USAGE)),
+                    _ if rom.is_none() => rom = Some(arg),
+                    _ => return Err(format!("unexpected argument: {}\n\n{}", arg, USAGE)),
+                }
+            }
+            let rom = rom.ok_or_else(|| USAGE.to_string())?;
+            Ok(Subcommand::Disasm { rom, load_address, entry_point: entry_point.unwrap_or(load_address) })
+        }
+        Some("asm") => {
+            args.next();
+            let mut source = None;
+            let mut output = None;
+            let mut load_address = PROGRAM_START;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-o" | "--output" => output = Some(args.next().ok_or("-o needs a value")?),
+                    "--load-address" => load_address = parse_address(args.next(), "--load-address")?,
+                    _ if arg.starts_with('-') => return Err(format!("unknown option: {}\n\n{}", arg, USAGE)),
+                    _ if source.is_none() => source = Some(arg),
+                    _ => return Err(format!("unexpected argument: {}\n\n{}", arg, USAGE)),
+                }
+            }
+            let source = source.ok_or_else(|| USAGE.to_string())?;
+            Ok(Subcommand::Asm { source, output, load_address })
+        }
+        _ => parse_args(args).map(Subcommand::Run),
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut rom = None;
+    let mut speed = DEFAULT_CLOCK_RATE;
+    let mut quirks = Quirks::default();
+    let mut load_address = PROGRAM_START;
+    let mut entry_point = None;
+    let mut font = Font::default();
+    let mut font_address = FONT_ADDRESS as u16;
+    let mut trace = None;
+    let mut headless = false;
+    let mut cycles = None;
+    let mut dump_screen = None;
+    let mut dump_registers = None;
+    let mut audio = None;
+    let mut tone = Tone::default();
+    let mut scale = None;
+    let mut palette = Palette::default();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-s" | "--speed" => {
+                let value = args.next().ok_or("--speed needs a value")?;
+                speed = value.parse().map_err(|_| format!("invalid speed: {}", value))?;
+                if speed == 0 {
+                    return Err("speed must be greater than zero".to_string());
+                }
+            }
+            "-q" | "--quirks" => {
+                let value = args.next().ok_or("--quirks needs a value")?;
+                quirks = Quirks::from_name(&value).ok_or_else(|| format!("unknown quirks profile: {}", value))?;
+            }
+            "--load-address" => load_address = parse_address(args.next(), "--load-address")?,
+            "--entry" => entry_point = Some(parse_address(args.next(), "--entry")?),
+            "--font" => font = parse_font(&args.next().ok_or("--font needs a value")?)?,
+            "--font-address" => font_address = parse_address(args.next(), "--font-address")?,
+            "-t" | "--trace" => trace = Some(args.next().ok_or("--trace needs a value")?),
+            "--headless" => headless = true,
+            "--cycles" => {
+                let value = args.next().ok_or("--cycles needs a value")?;
+                cycles = Some(value.parse().map_err(|_| format!("invalid cycle count: {}", value))?);
+            }
+            "--dump-screen" => dump_screen = Some(args.next().ok_or("--dump-screen needs a value")?),
+            "--dump-registers" => dump_registers = Some(args.next().ok_or("--dump-registers needs a value")?),
+            "--audio" => audio = Some(args.next().ok_or("--audio needs a value")?),
+            "--tone" => {
+                let value = args.next().ok_or("--tone needs a value")?;
+                tone.frequency = value
+                    .parse()
+                    .ok()
+                    .filter(|&frequency: &f32| frequency > 0.0 && frequency.is_finite())
+                    .ok_or_else(|| format!("invalid tone frequency: {}", value))?;
+            }
+            "--waveform" => {
+                let value = args.next().ok_or("--waveform needs a value")?;
+                tone.waveform = Waveform::from_name(&value).ok_or_else(|| format!("unknown waveform: {}", value))?;
+            }
+            "--scale" => {
+                let value = args.next().ok_or("--scale needs a value")?;
+                scale = Some(value.parse().ok().filter(|&scale| scale > 0).ok_or_else(|| format!("invalid scale: {}", value))?);
+            }
+            "--palette" => {
+                let value = args.next().ok_or("--palette needs a value")?;
+                palette = Palette::parse(&value).ok_or_else(|| format!("invalid palette: {}", value))?;
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}\n\n{}", arg, USAGE)),
+            _ if rom.is_none() => rom = Some(arg),
+            _ => return Err(format!("unexpected argument: {}\n\n{}", arg, USAGE)),
+        }
+    }
+    let rom = rom.ok_or_else(|| USAGE.to_string())?;
+    if !headless && (cycles.is_some() || dump_screen.is_some() || dump_registers.is_some() || audio.is_some()) {
+        return Err("--cycles, --dump-screen, --dump-registers and --audio need --headless".to_string());
+    }
+    let cycles = cycles.unwrap_or(HEADLESS_CYCLES);
+    let entry_point = entry_point.unwrap_or(load_address);
+    Ok(Options { rom, speed, quirks, load_address, entry_point, font, font_address, trace, headless, cycles, dump_screen, dump_registers, audio, tone, scale, palette })
+}
+
+/// 解析地址选项的值，规则与调试器相同（见 `debugger::parse_address`）
+fn parse_address(value: Option<String>, option: &str) -> Result<u16, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", option))?;
+    debugger::parse_address(&value).ok_or_else(|| format!("invalid address: {}", value))
+}
+
+/// 按名称查找内置字体，否则从文件读取自定义字体
+fn parse_font(value: &str) -> Result<Font, String> {
+    if let Some(font) = Font::from_name(value) {
+        return Ok(font);
+    }
+    let data = std::fs::read(value).map_err(|e| format!("{}: {}", value, e))?;
+    Font::from_bytes(&data).ok_or_else(|| format!("invalid font file: {} (80 or 240 bytes expected)", value))
+}
+
+/// 创建解释器，放置字体并加载 rom
+fn boot(rom: &[u8], options: &Options) -> Result<Chip8, Box<dyn Error>> {
+    let mut chip8 = Chip8::new_with(options.quirks);
+    chip8.set_font(&options.font, options.font_address)?;
+    chip8.load_rom_at(rom, options.load_address, options.entry_point)?;
+    Ok(chip8)
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let rom = std::fs::read(&options.rom).map_err(|e| format!("{}: {}", options.rom, e))?;
+    let mut chip8 = boot(&rom, options)?;
+    let mut scheduler = Scheduler::with_clock_rate(options.speed);
+    if let Some(path) = &options.trace {
+        scheduler.set_tracer(Some(Tracer::create(path).map_err(|e| format!("{}: {}", path, e))?));
+    }
+    let mut terminal = Terminal::new()?;
+    let mut bell = Bell::default();
+    let mut rewind = Rewind::new(REWIND_DEPTH, REWIND_INTERVAL);
+    let mut paused = false;
+    let mut status_changed = true;
+    let mut message = String::new();
+    let state_path = format!("{}.state", options.rom);
+    let mut last = Instant::now();
+
+    'frames: loop {
+        for command in terminal.poll(Scheduler::frame_duration())? {
+            match command {
+                Command::Quit => break 'frames,
+                Command::TogglePause => paused = !paused,
+                Command::Reset => {
+                    chip8 = boot(&rom, options)?;
+                    rewind.clear();
+                }
+                Command::Rewind => {
+                    if !rewind.rewind(&mut chip8) {
+                        message = "nothing to rewind".to_string();
+                    }
+                }
+                Command::SaveState => {
+                    message = match std::fs::write(&state_path, chip8.save_state()) {
+                        Ok(()) => format!("saved {}", state_path),
+                        Err(e) => format!("save failed: {}", e),
+                    }
+                }
+                Command::LoadState => {
+                    let result = std::fs::read(&state_path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|data| chip8.load_state(&data).map_err(|e| e.to_string()));
+                    message = match result {
+                        Ok(()) => format!("loaded {}", state_path),
+                        Err(e) => format!("load failed: {}", e),
+                    }
+                }
+                Command::Screenshot => {
+                    let path = format!("{}.png", options.rom);
+                    let scale = options.scale.unwrap_or(SCREENSHOT_SCALE);
+                    let image = Format::Png.export(chip8.screen(), chip8.screen_width(), chip8.screen_height(), scale, &options.palette);
+                    message = match std::fs::write(&path, image) {
+                        Ok(()) => format!("saved {}", path),
+                        Err(e) => format!("screenshot failed: {}", e),
+                    }
+                }
+                Command::SpeedUp => scheduler.set_clock_rate(scheduler.clock_rate() + 100),
+                Command::SlowDown => scheduler.set_clock_rate(scheduler.clock_rate().saturating_sub(100).max(100)),
+            }
+            status_changed = true;
+        }
+
+        let now = Instant::now();
+        let elapsed = now - last;
+        last = now;
+        if !paused {
+            rewind.record(&chip8);
+            frontend::run_frame(&mut chip8, &mut scheduler, elapsed, &mut terminal)?;
+            if chip8.has_exited() {
+                break;
+            }
+        }
+
+        frontend::present(&mut chip8, &mut terminal, &mut bell)?;
+        if status_changed {
+            let state = if paused { "paused" } else { "running" };
+            terminal.draw_status(&format!(
+                "{} | {} Hz | {} | P pause  F5 reset  F2/F3 save/load  +/- speed  Esc quit  {}",
+                options.rom,
+                scheduler.clock_rate(),
+                state,
+                message
+            ))?;
+            status_changed = false;
+        }
+    }
+    if let Some(tracer) = scheduler.take_tracer() {
+        tracer.finish()?;
+    }
+    Ok(())
+}
+
+fn run_headless(options: &Options) -> Result<(), Box<dyn Error>> {
+    let rom = std::fs::read(&options.rom).map_err(|e| format!("{}: {}", options.rom, e))?;
+    let mut chip8 = boot(&rom, options)?;
+    let mut tracer = match &options.trace {
+        Some(path) => Some(Tracer::create(path).map_err(|e| format!("{}: {}", path, e))?),
+        None => None,
+    };
+    let mut recorder = match &options.audio {
+        Some(path) => Some(WavRecorder::create(path, options.tone).map_err(|e| format!("{}: {}", path, e))?),
+        None => None,
+    };
+    let audio: &mut dyn AudioSink = match &mut recorder {
+        Some(recorder) => recorder,
+        None => &mut NullBackend,
+    };
+    let outcome = headless::run(&mut chip8, options.cycles, options.speed, tracer.as_mut(), audio)?;
+    if let Some(tracer) = tracer {
+        tracer.finish()?;
+    }
+    if let Some(recorder) = recorder {
+        recorder.finish()?;
+    }
+    if let Some(path) = &options.dump_screen {
+        let format = Format::from_path(Path::new(path));
+        let screen = format.export(chip8.screen(), chip8.screen_width(), chip8.screen_height(), options.scale.unwrap_or(1), &options.palette);
+        std::fs::write(path, screen).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    let registers = headless::registers_json(&chip8, &outcome);
+    match &options.dump_registers {
+        Some(path) => std::fs::write(path, registers).map_err(|e| format!("{}: {}", path, e))?,
+        None => print!("{}", registers),
+    }
+    Ok(())
+}
+
+fn debug(options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.trace.is_some() || options.headless {
+        return Err("--trace and --headless are not supported by the debugger".into());
+    }
+    let rom = std::fs::read(&options.rom).map_err(|e| format!("{}: {}", options.rom, e))?;
+    let chip8 = boot(&rom, options)?;
+    Debugger::new(chip8, options.speed).repl()?;
+    Ok(())
+}
+
+fn disasm(path: &str, load_address: u16, entry_point: u16) -> Result<(), Box<dyn Error>> {
+    let rom = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    print!("{}", disasm::disassemble(&rom, load_address, entry_point));
+    Ok(())
+}
+
+fn assemble(source: &str, output: Option<&str>, load_address: u16) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(source);
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", source, e))?;
+    let rom = asm::assemble_at(&text, path, load_address)?;
+    let output = output.map_or_else(|| path.with_extension("ch8"), PathBuf::from);
+    std::fs::write(&output, &rom).map_err(|e| format!("{}: {}", output.display(), e))?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let command = match parse_command(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+    let result = match command {
+        Subcommand::Run(options) if options.headless => run_headless(&options),
+        Subcommand::Run(options) => run(&options),
+        Subcommand::Debug(options) => debug(&options),
+        Subcommand::Disasm { rom, load_address, entry_point } => disasm(&rom, load_address, entry_point),
+        Subcommand::Asm { source, output, load_address } => assemble(&source, output.as_deref(), load_address),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
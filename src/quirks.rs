@@ -0,0 +1,148 @@
+use crate::constant::{CHIP8_MEMORY, XOCHIP_MEMORY};
+
+/// 兼容性配置
+///
+/// 不同平台上的 CHIP-8 解释器对部分指令的解释并不相同，游戏往往依赖于它所针对的平台的行为。
+///
+/// 参阅：<https://github.com/Timendus/chip8-test-suite#quirks-test>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8XY6 / 8XYE 先将 VY 复制到 VX 再移位，否则只对 VX 移位
+    pub shift_uses_vy: bool,
+    /// BNNN 按 BXNN 解释，跳转到 XNN + VX，否则跳转到 NNN + V0
+    pub jump_uses_vx: bool,
+    /// FX55 / FX65 执行后 I 增加 X + 1，否则 I 保持不变
+    pub load_store_increments_i: bool,
+    /// 8XY1 / 8XY2 / 8XY3 执行后 VF 清零
+    pub logic_resets_vf: bool,
+    /// DXYN 等待下一次垂直同步（60Hz）才绘制，每帧最多绘制一次
+    pub display_wait: bool,
+    /// 精灵超出屏幕边缘的部分被裁剪，否则回绕到另一侧
+    pub clip_sprites: bool,
+    /// XO-CHIP 模式，使用 64KB 内存而不是 4KB
+    pub xochip: bool,
+    /// SUPER-CHIP 1.1 的低分辨率行为：DXY0 绘制 8 x 16 的精灵，滚动距离按高分辨率像素计算（减半）
+    pub schip_lores: bool,
+}
+
+impl Quirks {
+    /// 原始的 COSMAC VIP 解释器
+    pub fn cosmac_vip() -> Self {
+        Self {
+            shift_uses_vy: true,
+            jump_uses_vx: false,
+            load_store_increments_i: true,
+            logic_resets_vf: true,
+            display_wait: true,
+            clip_sprites: true,
+            xochip: false,
+            schip_lores: false,
+        }
+    }
+
+    /// HP48 计算器上的 CHIP-48
+    pub fn chip48() -> Self {
+        Self {
+            shift_uses_vy: false,
+            jump_uses_vx: true,
+            load_store_increments_i: false,
+            logic_resets_vf: false,
+            display_wait: false,
+            clip_sprites: true,
+            xochip: false,
+            schip_lores: false,
+        }
+    }
+
+    /// SUPER-CHIP 1.1：与 CHIP-48 相同，低分辨率下 DXY0 绘制 8 x 16 的精灵，滚动距离减半
+    pub fn superchip() -> Self {
+        Self {
+            schip_lores: true,
+            ..Self::chip48()
+        }
+    }
+
+    /// Octo 的 XO-CHIP
+    pub fn xochip() -> Self {
+        Self {
+            shift_uses_vy: true,
+            jump_uses_vx: false,
+            load_store_increments_i: true,
+            logic_resets_vf: false,
+            display_wait: false,
+            clip_sprites: false,
+            xochip: true,
+            schip_lores: false,
+        }
+    }
+
+    /// 内存大小
+    pub fn memory_size(&self) -> usize {
+        if self.xochip {
+            XOCHIP_MEMORY
+        } else {
+            CHIP8_MEMORY
+        }
+    }
+
+    /// 将配置编码为位标志，用于存档
+    pub(crate) fn to_bits(self) -> u8 {
+        [
+            self.shift_uses_vy,
+            self.jump_uses_vx,
+            self.load_store_increments_i,
+            self.logic_resets_vf,
+            self.display_wait,
+            self.clip_sprites,
+            self.xochip,
+            self.schip_lores,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, &flag)| bits | (flag as u8) << i)
+    }
+
+    /// 从位标志解码配置
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        let flag = |i: u8| bits & (1 << i) != 0;
+        Self {
+            shift_uses_vy: flag(0),
+            jump_uses_vx: flag(1),
+            load_store_increments_i: flag(2),
+            logic_resets_vf: flag(3),
+            display_wait: flag(4),
+            clip_sprites: flag(5),
+            xochip: flag(6),
+            schip_lores: flag(7),
+        }
+    }
+
+    /// 按名称查找预设：`modern`、`vip`、`chip48`、`schip`、`xochip`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "modern" => Some(Self::default()),
+            "vip" | "cosmac" | "chip8" => Some(Self::cosmac_vip()),
+            "chip48" => Some(Self::chip48()),
+            "schip" | "superchip" => Some(Self::superchip()),
+            "xochip" | "xo-chip" => Some(Self::xochip()),
+            _ => None,
+        }
+    }
+}
+
+/// 默认配置与多数现代解释器一致：移位只使用 VX，BNNN 使用 V0，
+/// FX55 / FX65 不修改 I，逻辑运算不影响 VF，不等待垂直同步，裁剪精灵。
+impl Default for Quirks {
+    fn default() -> Self {
+        Self {
+            shift_uses_vy: false,
+            jump_uses_vx: false,
+            load_store_increments_i: false,
+            logic_resets_vf: false,
+            display_wait: false,
+            clip_sprites: true,
+            xochip: false,
+            schip_lores: false,
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+
+use crate::chip8::Chip8;
+
+/// 编码差异时整块比较的字节数
+const DELTA_CHUNK: usize = 64;
+
+/// 回溯缓冲区
+///
+/// 定期保存机器状态，用于前端倒带和调试器单步后退。只有最新的快照保存完整的存档，
+/// 更早的快照都保存为相对于后一个快照的差异（反向增量），每帧通常只有少量内存和屏幕发生变化，
+/// 因此占用的内存很小。快照数量不超过 `depth`，超出时丢弃最旧的快照。
+pub struct Rewind {
+    // 最多保存的快照数
+    depth: usize,
+    // 每调用多少次 `record` 保存一次快照
+    interval: u32,
+    // 距离上次保存快照调用 `record` 的次数
+    counter: u32,
+    // 刚刚倒带，下一次 `record` 不保存快照
+    rewound: bool,
+    // 最新的完整快照
+    latest: Option<Vec<u8>>,
+    // deltas[i] 将第 i + 1 个快照还原为第 i 个快照，最旧的在前
+    deltas: VecDeque<Vec<u8>>,
+}
+
+impl Rewind {
+    /// 创建回溯缓冲区，最多保存 `depth` 个快照，每 `interval` 次 `record` 保存一次
+    pub fn new(depth: usize, interval: u32) -> Self {
+        Self {
+            depth: depth.max(1),
+            interval: interval.max(1),
+            counter: 0,
+            rewound: false,
+            latest: None,
+            deltas: VecDeque::new(),
+        }
+    }
+
+    /// 每帧（或每条指令）调用一次，按间隔保存快照
+    ///
+    /// 倒带之后的第一次调用不保存快照，否则刚恢复的状态会被重新保存，连续倒带时停在原地。
+    pub fn record(&mut self, chip8: &Chip8) {
+        if std::mem::take(&mut self.rewound) {
+            return;
+        }
+        if self.counter == 0 {
+            self.push(chip8);
+        }
+        self.counter = (self.counter + 1) % self.interval;
+    }
+
+    /// 立即保存一个快照
+    pub fn push(&mut self, chip8: &Chip8) {
+        let state = chip8.save_state();
+        if let Some(previous) = self.latest.take() {
+            self.deltas.push_back(encode_delta(&state, &previous));
+            if self.deltas.len() >= self.depth {
+                self.deltas.pop_front();
+            }
+        }
+        self.latest = Some(state);
+    }
+
+    /// 恢复到最近的快照并将其移出缓冲区，没有快照时返回 false
+    pub fn rewind(&mut self, chip8: &mut Chip8) -> bool {
+        let Some(state) = self.latest.take() else {
+            return false;
+        };
+        self.latest = self.deltas.pop_back().map(|delta| apply_delta(&state, &delta));
+        self.counter = 0;
+        self.rewound = true;
+        // 快照来自 save_state，恢复不会失败
+        chip8.load_state(&state).is_ok()
+    }
+
+    /// 保存的快照数
+    pub fn len(&self) -> usize {
+        self.deltas.len() + self.latest.is_some() as usize
+    }
+
+    /// 是否没有快照
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_none()
+    }
+
+    /// 清空所有快照
+    pub fn clear(&mut self) {
+        self.latest = None;
+        self.deltas.clear();
+        self.counter = 0;
+        self.rewound = false;
+    }
+
+    /// 快照占用的字节数
+    pub fn memory_usage(&self) -> usize {
+        self.latest.as_ref().map_or(0, Vec::len) + self.deltas.iter().map(Vec::len).sum::<usize>()
+    }
+}
+
+/// 编码从 `base` 到 `target` 的差异
+///
+/// 格式：目标长度，之后是若干段（与 base 相同的字节数，不同的字节数，不同的字节），数字均为 LEB128 变长整数。
+fn encode_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut delta = Vec::new();
+    write_varint(&mut delta, target.len());
+    let same = |i: usize| i < base.len() && base[i] == target[i];
+    let common = base.len().min(target.len());
+    let mut i = 0;
+    while i < target.len() {
+        let start = i;
+        // 大部分字节没有变化，先整块比较
+        while i + DELTA_CHUNK <= common && base[i..i + DELTA_CHUNK] == target[i..i + DELTA_CHUNK] {
+            i += DELTA_CHUNK;
+        }
+        while i < target.len() && same(i) {
+            i += 1;
+        }
+        let skip = i - start;
+        let start = i;
+        while i < target.len() && !same(i) {
+            i += 1;
+        }
+        write_varint(&mut delta, skip);
+        write_varint(&mut delta, i - start);
+        delta.extend_from_slice(&target[start..i]);
+    }
+    delta
+}
+
+/// 将 `encode_delta` 生成的差异应用到 `base` 上
+fn apply_delta(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut position = 0;
+    let len = read_varint(delta, &mut position);
+    let mut target = Vec::with_capacity(len);
+    while target.len() < len {
+        let skip = read_varint(delta, &mut position);
+        let start = target.len();
+        target.extend_from_slice(&base[start..start + skip]);
+        let changed = read_varint(delta, &mut position);
+        target.extend_from_slice(&delta[position..position + changed]);
+        position += changed;
+    }
+    target
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn read_varint(buffer: &[u8], position: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = buffer[*position];
+        *position += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quirks::Quirks;
+
+    /// 不断递增 V0 并绘制的 rom
+    fn counter_rom() -> Chip8 {
+        let mut chip8 = Chip8::new();
+        // 7001 F029 D015 1200
+        chip8.load_rom(&[0x70, 0x01, 0xF0, 0x29, 0xD0, 0x15, 0x12, 0x00]).unwrap();
+        chip8
+    }
+
+    #[test]
+    fn delta_round_trips() {
+        let base = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        for target in [vec![1, 2, 9, 4, 5, 6, 0, 8], vec![], vec![1, 2], vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10], base.clone()] {
+            assert_eq!(apply_delta(&base, &encode_delta(&base, &target)), target);
+        }
+
+        // 超过一个比较块的数据
+        let base: Vec<u8> = (0..=255).collect();
+        let mut target = base.clone();
+        target[70] = 0;
+        target[200..210].fill(0);
+        target.truncate(250);
+        assert_eq!(apply_delta(&base, &encode_delta(&base, &target)), target);
+    }
+
+    #[test]
+    fn rewinds_in_reverse_order() {
+        let mut chip8 = counter_rom();
+        let mut rewind = Rewind::new(100, 1);
+        let mut states = Vec::new();
+        for _ in 0..30 {
+            rewind.record(&chip8);
+            states.push(chip8.save_state());
+            chip8.step().unwrap();
+        }
+        assert_eq!(rewind.len(), 30);
+        while let Some(expected) = states.pop() {
+            assert!(rewind.rewind(&mut chip8));
+            assert_eq!(chip8.save_state(), expected);
+        }
+        assert!(rewind.is_empty());
+        assert!(!rewind.rewind(&mut chip8));
+    }
+
+    #[test]
+    fn repeated_rewinds_keep_moving_backwards() {
+        let mut chip8 = counter_rom();
+        let mut rewind = Rewind::new(100, 1);
+        let mut states = Vec::new();
+        for _ in 0..40 {
+            rewind.record(&chip8);
+            states.push(chip8.save_state());
+            chip8.step().unwrap();
+        }
+        // 与前端的主循环一样，倒带之后仍然调用 record 并继续执行
+        let mut last = states.len();
+        while rewind.rewind(&mut chip8) {
+            let index = states.iter().position(|state| *state == chip8.save_state()).unwrap();
+            assert_eq!(index + 1, last);
+            last = index;
+            rewind.record(&chip8);
+            chip8.step().unwrap();
+        }
+        assert_eq!(last, 0);
+    }
+
+    #[test]
+    fn depth_and_interval_bound_snapshots() {
+        let mut chip8 = counter_rom();
+        let mut rewind = Rewind::new(5, 3);
+        let mut states = Vec::new();
+        for i in 0..30 {
+            if i % 3 == 0 {
+                states.push(chip8.save_state());
+            }
+            rewind.record(&chip8);
+            chip8.step().unwrap();
+        }
+        assert_eq!(rewind.len(), 5);
+        for expected in states.iter().rev().take(5) {
+            assert!(rewind.rewind(&mut chip8));
+            assert_eq!(&chip8.save_state(), expected);
+        }
+        assert!(rewind.is_empty());
+    }
+
+    #[test]
+    fn deltas_are_much_smaller_than_full_states() {
+        let mut chip8 = Chip8::new_with(Quirks::xochip());
+        chip8.load_rom(&[0x70, 0x01, 0x12, 0x00]).unwrap();
+        let mut rewind = Rewind::new(64, 1);
+        for _ in 0..64 {
+            rewind.record(&chip8);
+            chip8.step().unwrap();
+        }
+        let full = chip8.save_state().len();
+        assert!(rewind.memory_usage() < full * 2, "{} bytes", rewind.memory_usage());
+    }
+
+    #[test]
+    fn survives_resolution_change() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x00, 0xFF, 0x00, 0xFE, 0x12, 0x00]).unwrap();
+        let mut rewind = Rewind::new(10, 1);
+        let mut states = Vec::new();
+        for _ in 0..3 {
+            rewind.record(&chip8);
+            states.push(chip8.save_state());
+            chip8.step().unwrap();
+        }
+        while let Some(expected) = states.pop() {
+            assert!(rewind.rewind(&mut chip8));
+            assert_eq!(chip8.save_state(), expected);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use crate::chip8::Chip8;
+use crate::constant::{DEFAULT_CLOCK_RATE, TIMER_FREQUENCY};
+use crate::error::Chip8Error;
+use crate::trace::Tracer;
+
+/// 调度器
+///
+/// 定时器固定以 60Hz 递减，与 CPU 频率无关。每一帧（1/60 秒）先执行
+/// `clock_rate / 60` 条指令，再递减一次定时器。频率不能被 60 整除时，
+/// 余下的指令数会累积到后续帧中，保证长期来看每秒执行的指令数准确。
+pub struct Scheduler {
+    // 每秒执行的指令数
+    clock_rate: u32,
+    // 尚未分配到帧中的指令数（乘以 60）
+    cycle_remainder: u32,
+    // 尚未消耗的实际时间
+    elapsed: Duration,
+    // 设置后通过它执行指令，记录每条指令
+    tracer: Option<Tracer>,
+}
+
+/// 默认频率的调度器
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    /// 创建默认频率的调度器
+    pub fn new() -> Self {
+        Self::with_clock_rate(DEFAULT_CLOCK_RATE)
+    }
+
+    /// 创建指定频率（Hz）的调度器
+    pub fn with_clock_rate(clock_rate: u32) -> Self {
+        Self {
+            clock_rate: clock_rate.max(1),
+            cycle_remainder: 0,
+            elapsed: Duration::ZERO,
+            tracer: None,
+        }
+    }
+
+    /// CPU 频率
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    /// 修改 CPU 频率，从下一帧开始生效
+    pub fn set_clock_rate(&mut self, clock_rate: u32) {
+        self.clock_rate = clock_rate.max(1);
+    }
+
+    /// 设置执行跟踪，之后执行的每条指令都会被记录
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    /// 取出执行跟踪
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// 运行一帧：执行本帧的指令，然后递减定时器
+    ///
+    /// 返回本帧执行的指令数。
+    pub fn run_frame(&mut self, chip8: &mut Chip8) -> Result<u32, Chip8Error> {
+        self.cycle_remainder += self.clock_rate;
+        let cycles = self.cycle_remainder / TIMER_FREQUENCY;
+        self.cycle_remainder %= TIMER_FREQUENCY;
+        for _ in 0..cycles {
+            match &mut self.tracer {
+                Some(tracer) => tracer.step(chip8)?,
+                None => chip8.step()?,
+            };
+        }
+        chip8.tick_timers();
+        Ok(cycles)
+    }
+
+    /// 根据实际经过的时间运行若干帧
+    ///
+    /// 不足一帧的时间会保留到下一次调用，返回本次运行的帧数。
+    pub fn update(&mut self, chip8: &mut Chip8, elapsed: Duration) -> Result<u32, Chip8Error> {
+        let frame = Self::frame_duration();
+        self.elapsed += elapsed;
+        let mut frames = 0;
+        while self.elapsed >= frame {
+            self.elapsed -= frame;
+            self.run_frame(chip8)?;
+            frames += 1;
+        }
+        Ok(frames)
+    }
+
+    /// 一帧的时长
+    pub fn frame_duration() -> Duration {
+        Duration::from_secs(1) / TIMER_FREQUENCY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 7000：V0 += 0 作为空操作，1200 跳回起点
+    fn idle_rom() -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x70, 0x00, 0x12, 0x00]).unwrap();
+        chip8
+    }
+
+    #[test]
+    fn runs_clock_rate_instructions_per_second() {
+        let mut chip8 = idle_rom();
+        let mut scheduler = Scheduler::with_clock_rate(500);
+        let mut total = 0;
+        for _ in 0..TIMER_FREQUENCY {
+            total += scheduler.run_frame(&mut chip8).unwrap();
+        }
+        assert_eq!(total, 500);
+    }
+
+    #[test]
+    fn timers_tick_once_per_frame_regardless_of_clock_rate() {
+        for clock_rate in [60, 600, 6000] {
+            let mut chip8 = Chip8::new();
+            chip8.set_v(0, 10);
+            // F015：延迟定时器 = V0，之后循环执行 F107：V1 = 延迟定时器
+            chip8.load_rom(&[0xF0, 0x15, 0xF1, 0x07, 0x12, 0x02]).unwrap();
+            let mut scheduler = Scheduler::with_clock_rate(clock_rate);
+            for _ in 0..5 {
+                scheduler.run_frame(&mut chip8).unwrap();
+            }
+            chip8.step().unwrap();
+            chip8.step().unwrap();
+            assert_eq!(chip8.v(1), 5, "clock rate {}", clock_rate);
+        }
+    }
+
+    #[test]
+    fn update_carries_partial_frames() {
+        let mut chip8 = idle_rom();
+        let mut scheduler = Scheduler::new();
+        let half = Scheduler::frame_duration() / 2;
+        assert_eq!(scheduler.update(&mut chip8, half).unwrap(), 0);
+        assert_eq!(scheduler.update(&mut chip8, half + Duration::from_micros(1)).unwrap(), 1);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::error::Chip8Error;
+
+/// 存档文件头
+pub(crate) const STATE_MAGIC: &[u8; 4] = b"CH8S";
+/// 存档格式版本，格式变化时递增
+pub(crate) const STATE_VERSION: u16 = 3;
+
+/// 存档写入器，所有多字节整数按大端写入
+pub(crate) struct StateWriter {
+    buffer: Vec<u8>,
+}
+
+impl StateWriter {
+    /// 创建写入器并写入文件头和版本号
+    pub(crate) fn new() -> Self {
+        let mut writer = Self { buffer: Vec::new() };
+        writer.bytes(STATE_MAGIC);
+        writer.u16(STATE_VERSION);
+        writer
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+/// 存档读取器
+pub(crate) struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /// 创建读取器并校验文件头和版本号
+    pub(crate) fn new(data: &'a [u8]) -> Result<Self, Chip8Error> {
+        let mut reader = Self { data, position: 0 };
+        if reader.bytes(STATE_MAGIC.len())? != STATE_MAGIC {
+            return Err(Chip8Error::InvalidState("bad magic header"));
+        }
+        if reader.u16()? != STATE_VERSION {
+            return Err(Chip8Error::InvalidState("unsupported version"));
+        }
+        Ok(reader)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, Chip8Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, Chip8Error> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Chip8Error::InvalidState("bad boolean")),
+        }
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, Chip8Error> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, Chip8Error> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], Chip8Error> {
+        let end = self.position.checked_add(len).filter(|&end| end <= self.data.len());
+        let end = end.ok_or(Chip8Error::InvalidState("unexpected end of data"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// 确认所有数据都已读取
+    pub(crate) fn finish(self) -> Result<(), Chip8Error> {
+        if self.position != self.data.len() {
+            return Err(Chip8Error::InvalidState("trailing data"));
+        }
+        Ok(())
+    }
+}
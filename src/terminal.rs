@@ -0,0 +1,250 @@
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, execute, queue, style, terminal};
+
+use chip8_rs::frontend::{AudioSink, DisplaySink, InputSource};
+use chip8_rs::Chip8;
+
+/// 不支持按键松开事件的终端中，按键在最后一次按下（或自动重复）之后保持按下的时长
+const KEY_HOLD: Duration = Duration::from_millis(150);
+
+/// 前端控制命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// 退出
+    Quit,
+    /// 暂停 / 继续
+    TogglePause,
+    /// 重新加载 rom 并复位
+    Reset,
+    /// 保存存档
+    SaveState,
+    /// 读取存档
+    LoadState,
+    /// 倒带
+    Rewind,
+    /// 保存截图
+    Screenshot,
+    /// 提高 CPU 频率
+    SpeedUp,
+    /// 降低 CPU 频率
+    SlowDown,
+}
+
+/// 将 QWERTY 键盘映射到 CHIP-8 的 16 键键盘
+///
+/// ```text
+/// 1 2 3 4        1 2 3 C
+/// Q W E R   =>   4 5 6 D
+/// A S D F        7 8 9 E
+/// Z X C V        A 0 B F
+/// ```
+pub fn map_key(c: char) -> Option<usize> {
+    let key = match c.to_ascii_lowercase() {
+        '1' => 0x1,
+        '2' => 0x2,
+        '3' => 0x3,
+        '4' => 0xC,
+        'q' => 0x4,
+        'w' => 0x5,
+        'e' => 0x6,
+        'r' => 0xD,
+        'a' => 0x7,
+        's' => 0x8,
+        'd' => 0x9,
+        'f' => 0xE,
+        'z' => 0xA,
+        'x' => 0x0,
+        'c' => 0xB,
+        'v' => 0xF,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// 终端前端
+///
+/// 创建时进入原始模式和备用屏幕，销毁时恢复终端。
+pub struct Terminal {
+    stdout: Stdout,
+    // 终端是否会报告按键松开事件
+    reports_release: bool,
+    // 每个按键最后一次按下的时间，用于在不支持松开事件的终端中模拟松开
+    last_pressed: [Option<Instant>; 16],
+    keys: [bool; 16],
+    // 上一次绘制的分辨率，变化时需要清屏并移动状态栏
+    size: (usize, usize),
+    status: String,
+}
+
+impl Terminal {
+    /// 初始化终端
+    pub fn new() -> io::Result<Self> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide, terminal::Clear(terminal::ClearType::All))?;
+        let reports_release = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if reports_release {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+        Ok(Self {
+            stdout,
+            reports_release,
+            last_pressed: [None; 16],
+            keys: [false; 16],
+            size: (0, 0),
+            status: String::new(),
+        })
+    }
+
+    /// 处理输入事件，最多等待 `timeout`
+    ///
+    /// 按键状态通过 `InputSource` 读取，返回期间收到的控制命令。
+    pub fn poll(&mut self, timeout: Duration) -> io::Result<Vec<Command>> {
+        let mut commands = Vec::new();
+        let deadline = Instant::now() + timeout;
+        loop {
+            let now = Instant::now();
+            let remaining = deadline.saturating_duration_since(now);
+            if !event::poll(remaining)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                if let Some(command) = self.handle_key(key) {
+                    commands.push(command);
+                }
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+        if !self.reports_release {
+            let now = Instant::now();
+            for (key, pressed) in self.last_pressed.iter_mut().enumerate() {
+                if matches!(pressed, Some(at) if now.duration_since(*at) >= KEY_HOLD) {
+                    *pressed = None;
+                    self.keys[key] = false;
+                }
+            }
+        }
+        Ok(commands)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Command> {
+        let released = key.kind == KeyEventKind::Release;
+        if let KeyCode::Char(c) = key.code {
+            if key.modifiers.contains(KeyModifiers::CONTROL) && c == 'c' {
+                return Some(Command::Quit);
+            }
+            if let Some(k) = map_key(c) {
+                self.keys[k] = !released;
+                self.last_pressed[k] = if released { None } else { Some(Instant::now()) };
+                return None;
+            }
+        }
+        if released {
+            return None;
+        }
+        match key.code {
+            KeyCode::Esc => Some(Command::Quit),
+            KeyCode::Char('p') | KeyCode::Char('P') => Some(Command::TogglePause),
+            KeyCode::F(5) | KeyCode::Backspace => Some(Command::Reset),
+            KeyCode::F(2) => Some(Command::SaveState),
+            KeyCode::F(3) => Some(Command::LoadState),
+            KeyCode::F(12) => Some(Command::Screenshot),
+            KeyCode::Left => Some(Command::Rewind),
+            KeyCode::Char('+') | KeyCode::Char('=') => Some(Command::SpeedUp),
+            KeyCode::Char('-') => Some(Command::SlowDown),
+            _ => None,
+        }
+    }
+
+    /// 在屏幕下方绘制状态栏
+    pub fn draw_status(&mut self, status: &str) -> io::Result<()> {
+        self.status = status.to_string();
+        self.queue_status()?;
+        self.stdout.flush()
+    }
+
+    fn queue_status(&mut self) -> io::Result<()> {
+        queue!(
+            self.stdout,
+            cursor::MoveTo(0, (self.size.1 / 2) as u16),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            style::Print(&self.status)
+        )
+    }
+}
+
+/// 使用 Unicode 半块字符绘制屏幕，每个字符显示上下两个像素
+impl DisplaySink for Terminal {
+    fn draw(&mut self, screen: &[u8], width: usize, height: usize) -> io::Result<()> {
+        if self.size != (width, height) {
+            self.size = (width, height);
+            queue!(self.stdout, terminal::Clear(terminal::ClearType::All))?;
+            self.queue_status()?;
+        }
+        for row in 0..height / 2 {
+            let top = &screen[row * 2 * width..(row * 2 + 1) * width];
+            let bottom = &screen[(row * 2 + 1) * width..(row * 2 + 2) * width];
+            let line: String = top
+                .iter()
+                .zip(bottom)
+                .map(|(&top, &bottom)| match (top != 0, bottom != 0) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█',
+                })
+                .collect();
+            queue!(self.stdout, cursor::MoveTo(0, row as u16), style::Print(line))?;
+        }
+        self.stdout.flush()
+    }
+}
+
+impl InputSource for Terminal {
+    fn keys(&mut self) -> [bool; 16] {
+        self.keys
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if self.reports_release {
+            let _ = execute!(self.stdout, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(self.stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// 终端响铃
+///
+/// 终端只能发出短促的提示音，因此只在声音定时器从零变为非零时响铃一次。
+#[derive(Debug, Default)]
+pub struct Bell {
+    // 上一帧是否在发声
+    active: bool,
+}
+
+impl AudioSink for Bell {
+    fn update(&mut self, chip8: &Chip8) -> io::Result<()> {
+        let active = chip8.is_sound_active();
+        if active && !self.active {
+            let mut stdout = io::stdout();
+            queue!(stdout, style::Print('\x07'))?;
+            stdout.flush()?;
+        }
+        self.active = active;
+        Ok(())
+    }
+}
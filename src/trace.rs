@@ -0,0 +1,213 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::chip8::{Chip8, StepInfo};
+use crate::error::Chip8Error;
+use crate::instruction::Instruction;
+
+/// 写内存的指令最多修改 I 之后的 16 个字节
+const WRITE_WINDOW: usize = 16;
+
+/// 执行跟踪
+///
+/// 每执行一条指令写入一行，格式固定，便于与其他模拟器的跟踪结果逐行比较：
+///
+/// ```text
+/// 00000000 0200 6A02 LD VA, 0x02          VA=02
+/// 00000001 0202 A22A LD I, 0x22A          I=022A
+/// 00000002 0204 F233 LD B, V2             [022A]=01 [022B]=02 [022C]=08
+/// ```
+///
+/// 依次为周期数（十进制）、PC、操作码、助记符和发生变化的状态。
+/// 变化按 V0 - VF、I、SP、DT、ST、内存的顺序列出，PC 的变化不列出。
+pub struct Tracer {
+    output: Box<dyn Write>,
+    cycle: u64,
+    // 第一次写入失败的错误，之后不再写入
+    error: Option<io::Error>,
+}
+
+/// 执行前的状态，用于找出发生变化的部分
+struct Snapshot {
+    registers: [u8; 16],
+    address_register: u16,
+    stack_pointer: usize,
+    delay_timer: u8,
+    sound_timer: u8,
+    // 写内存指令执行前 I 之后的地址和内存，地址与解释器写入时一样在内存末尾回绕
+    memory: Vec<(usize, u8)>,
+}
+
+impl Snapshot {
+    fn new(chip8: &Chip8, writes_memory: bool) -> Self {
+        let memory = if writes_memory {
+            let (start, len) = (chip8.address_register() as usize, chip8.memory().len());
+            (0..WRITE_WINDOW.min(len)).map(|k| (start + k) % len).map(|address| (address, chip8.memory()[address])).collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            registers: *chip8.registers(),
+            address_register: chip8.address_register(),
+            stack_pointer: chip8.stack().len(),
+            delay_timer: chip8.delay_timer(),
+            sound_timer: chip8.sound_timer(),
+            memory,
+        }
+    }
+
+    /// 列出与 `chip8` 当前状态不同的部分
+    fn changes(&self, chip8: &Chip8) -> String {
+        let mut changes = String::new();
+        for (x, (&old, &new)) in self.registers.iter().zip(chip8.registers()).enumerate() {
+            if old != new {
+                let _ = write!(changes, " V{:X}={:02X}", x, new);
+            }
+        }
+        if self.address_register != chip8.address_register() {
+            let _ = write!(changes, " I={:04X}", chip8.address_register());
+        }
+        if self.stack_pointer != chip8.stack().len() {
+            let _ = write!(changes, " SP={}", chip8.stack().len());
+        }
+        if self.delay_timer != chip8.delay_timer() {
+            let _ = write!(changes, " DT={:02X}", chip8.delay_timer());
+        }
+        if self.sound_timer != chip8.sound_timer() {
+            let _ = write!(changes, " ST={:02X}", chip8.sound_timer());
+        }
+        for &(address, old) in &self.memory {
+            match chip8.memory().get(address) {
+                Some(&new) if new != old => {
+                    let _ = write!(changes, " [{:04X}]={:02X}", address, new);
+                }
+                _ => {}
+            }
+        }
+        changes
+    }
+}
+
+impl Tracer {
+    /// 将跟踪写入 `output`
+    pub fn new(output: impl Write + 'static) -> Self {
+        Self { output: Box::new(output), cycle: 0, error: None }
+    }
+
+    /// 将跟踪写入文件
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// 执行一条指令并记录
+    ///
+    /// 出错的指令不会被记录。
+    pub fn step(&mut self, chip8: &mut Chip8) -> Result<StepInfo, Chip8Error> {
+        let pc = chip8.program_counter();
+        let writes_memory = chip8.memory().get(pc as usize..pc as usize + 2).is_some_and(|opcode| {
+            let opcode = u16::from_be_bytes([opcode[0], opcode[1]]);
+            matches!(
+                Instruction::decode(opcode),
+                Ok(Instruction::LdBVx { .. } | Instruction::LdIVx { .. } | Instruction::SaveVxVy { .. })
+            )
+        });
+        let snapshot = Snapshot::new(chip8, writes_memory);
+        let info = chip8.step()?;
+        if self.error.is_none() {
+            let mnemonic = info.instruction.to_string();
+            let line = format!("{:08} {:04X} {:04X} {:<20}{}", self.cycle, info.pc, info.opcode, mnemonic, snapshot.changes(chip8));
+            if let Err(e) = writeln!(self.output, "{}", line.trim_end()) {
+                self.error = Some(e);
+            }
+        }
+        self.cycle += 1;
+        Ok(info)
+    }
+
+    /// 写入剩余的内容，返回跟踪过程中第一次写入失败的错误
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.output.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::quirks::Quirks;
+
+    /// 可以在 Tracer 之外读取内容的输出
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace(rom: &[u8], quirks: Quirks, steps: usize) -> Vec<String> {
+        let output = Shared::default();
+        let mut tracer = Tracer::new(output.clone());
+        let mut chip8 = Chip8::new_with(quirks);
+        chip8.load_rom(rom).unwrap();
+        for _ in 0..steps {
+            tracer.step(&mut chip8).unwrap();
+        }
+        tracer.finish().unwrap();
+        let text = String::from_utf8(output.0.borrow().clone()).unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn records_changed_registers() {
+        // 6A02 A22A 8AA4 220A / 00EE
+        let rom = [0x6A, 0x02, 0xA2, 0x2A, 0x8A, 0xA4, 0x22, 0x0A, 0x00, 0x00, 0x00, 0xEE];
+        assert_eq!(
+            trace(&rom, Quirks::default(), 5),
+            [
+                "00000000 0200 6A02 LD VA, 0x02          VA=02",
+                "00000001 0202 A22A LD I, 0x22A          I=022A",
+                "00000002 0204 8AA4 ADD VA, VA           VA=04",
+                "00000003 0206 220A CALL 0x20A           SP=1",
+                "00000004 020A 00EE RET                  SP=0",
+            ]
+        );
+    }
+
+    #[test]
+    fn records_memory_writes() {
+        // 6280 A300 F233 F155
+        let rom = [0x62, 0x80, 0xA3, 0x00, 0xF2, 0x33, 0xF1, 0x55];
+        let lines = trace(&rom, Quirks::cosmac_vip(), 4);
+        assert_eq!(lines[2], "00000002 0204 F233 LD B, V2             [0300]=01 [0301]=02 [0302]=08");
+        assert_eq!(lines[3], "00000003 0206 F155 LD [I], V1           I=0302 [0300]=00 [0301]=00");
+    }
+
+    #[test]
+    fn memory_writes_wrap_at_end_of_memory() {
+        // AFFF 60FF F01E F055：I 超出内存，写入回绕到 0x0FE
+        let rom = [0xAF, 0xFF, 0x60, 0xFF, 0xF0, 0x1E, 0xF0, 0x55];
+        assert_eq!(trace(&rom, Quirks::default(), 4)[3], "00000003 0206 F055 LD [I], V0           [00FE]=FF");
+
+        // AFFE 6001 6102 6203 F255：跨过内存末尾写入
+        let rom = [0xAF, 0xFE, 0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0xF2, 0x55];
+        assert_eq!(trace(&rom, Quirks::default(), 5)[4], "00000004 0208 F255 LD [I], V2           [0FFE]=01 [0FFF]=02 [0000]=03");
+    }
+
+    #[test]
+    fn unchanged_state_has_no_trailing_fields() {
+        assert_eq!(trace(&[0x12, 0x00], Quirks::default(), 2), ["00000000 0200 1200 JP 0x200", "00000001 0200 1200 JP 0x200"]);
+    }
+}
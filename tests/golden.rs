@@ -0,0 +1,190 @@
+//! 黄金图像测试
+//!
+//! 用 `asm` 子命令汇编 `tests/roms` 中的测试 rom，用 `run --headless` 运行到跳转到自身为止，
+//! 再将导出的 ASCII 屏幕与 `tests/golden` 中保存的图像逐字比较。
+//!
+//! 修改了测试 rom 或有意改变了行为时，设置环境变量 `UPDATE_GOLDEN=1` 运行测试以重新生成图像，
+//! 并在提交前检查图像的变化。
+//!
+//! 黄金图像由本实现生成，只能发现行为的变化。为了不完全依赖它们，测试还检查与实现无关的结果：
+//! `alu` 中每项检查的期望值是按规范手工计算的，屏幕上必须全部为 ✓；
+//! `quirks` 和 `draw` 的结果寄存器与按规范和兼容性配置得出的值比较。
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 测试 rom 都很短，超过这个周期数说明没有正常结束
+const CYCLES: &str = "100000";
+
+/// `alu.8s` 中的检查项数
+const ALU_CHECKS: usize = 23;
+
+/// check.8s 中表示通过（✓）和失败（✗）的精灵
+const PASS: [&str; 5] = ["....#", "...#.", "#.#..", ".#...", "....."];
+const FAIL: [&str; 5] = ["#...#", ".#.#.", "..#..", ".#.#.", "#...#"];
+
+/// 运行结束时的屏幕和通用寄存器
+struct Outcome {
+    screen: String,
+    registers: Vec<u8>,
+}
+
+/// 运行 chip8-rs，失败时输出错误信息，返回标准输出
+fn chip8(args: &[&Path]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_chip8-rs")).args(args).output().unwrap();
+    assert!(
+        output.status.success(),
+        "chip8-rs {:?} failed:\n{}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// 每个测试使用独立的临时目录，测试可以并行运行
+fn work_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("chip8-rs-golden-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 汇编并运行 `rom`，返回最终的屏幕和寄存器
+fn run(rom: &str, quirks: &str) -> Outcome {
+    let tests = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let dir = work_dir(&format!("{}-{}", rom, quirks));
+    let binary = dir.join("rom.ch8");
+    let screen = dir.join("screen.txt");
+
+    let source = tests.join("roms").join(format!("{}.8s", rom));
+    chip8(&[Path::new("asm"), &source, Path::new("-o"), &binary]);
+    let registers = chip8(&[
+        Path::new("run"),
+        Path::new("--headless"),
+        Path::new("--cycles"),
+        Path::new(CYCLES),
+        Path::new("--quirks"),
+        Path::new(quirks),
+        Path::new("--dump-screen"),
+        &screen,
+        &binary,
+    ]);
+    assert!(registers.contains("\"halt\": \"loop\""), "{} did not finish with {} quirks:\n{}", rom, quirks, registers);
+
+    let text = fs::read_to_string(&screen).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    Outcome { screen: text, registers: parse_registers(&registers) }
+}
+
+/// 从寄存器的 JSON 中取出 V0 - VF
+fn parse_registers(json: &str) -> Vec<u8> {
+    let start = json.find("\"v\": [").expect("no registers in output") + 6;
+    let end = start + json[start..].find(']').unwrap();
+    json[start..end].split(',').map(|value| value.trim().parse().unwrap()).collect()
+}
+
+/// 统计屏幕上 ✓ 和 ✗ 的个数
+fn verdicts(screen: &str) -> (usize, usize) {
+    let rows: Vec<&[u8]> = screen.lines().map(str::as_bytes).collect();
+    let (mut passed, mut failed) = (0, 0);
+    for window in rows.windows(5) {
+        let matches = |glyph: &[&str; 5], x: usize| glyph.iter().zip(window).all(|(line, row)| row.get(x..x + 5) == Some(line.as_bytes()));
+        for x in 0..window[0].len() {
+            passed += matches(&PASS, x) as usize;
+            failed += matches(&FAIL, x) as usize;
+        }
+    }
+    (passed, failed)
+}
+
+/// 将 `rom` 在 `quirks` 配置下的屏幕与黄金图像比较，返回运行结果
+fn check(rom: &str, quirks: &str) -> Outcome {
+    let outcome = run(rom, quirks);
+    let actual = &outcome.screen;
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}-{}.txt", rom, quirks));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&golden, actual).unwrap();
+        return outcome;
+    }
+    let expected = fs::read_to_string(&golden)
+        .unwrap_or_else(|e| panic!("cannot read {}: {} (run with UPDATE_GOLDEN=1 to create it)", golden.display(), e));
+    assert!(
+        *actual == expected,
+        "screen of {} with {} quirks differs from {}\n\nactual:\n{}\nexpected:\n{}",
+        rom,
+        quirks,
+        golden.display(),
+        actual,
+        expected
+    );
+    outcome
+}
+
+/// 所有检查项都通过
+fn assert_all_passed(outcome: &Outcome, checks: usize) {
+    assert_eq!(verdicts(&outcome.screen), (checks, 0), "expected {} passed and 0 failed checks:\n{}", checks, outcome.screen);
+}
+
+/// `quirks.8s` 的四项结果（V4 - V7）：BNNN 使用的寄存器、移位的来源、FX65 之后 I 的变化、8XY1 之后的 VF
+fn assert_quirks(outcome: &Outcome, expected: [u8; 4]) {
+    assert_eq!(outcome.registers[4..8], expected);
+}
+
+#[test]
+fn alu_modern() {
+    assert_all_passed(&check("alu", "modern"), ALU_CHECKS);
+}
+
+#[test]
+fn alu_vip() {
+    assert_all_passed(&check("alu", "vip"), ALU_CHECKS);
+}
+
+#[test]
+fn draw_clips() {
+    // 第二次绘制同一精灵时擦除并发生碰撞
+    assert_eq!(check("draw", "modern").registers[2..4], [0, 1]);
+}
+
+#[test]
+fn draw_wraps() {
+    assert_eq!(check("draw", "xochip").registers[2..4], [0, 1]);
+}
+
+#[test]
+fn quirks_modern() {
+    // 现代解释器：BNNN 使用 V0，只移位 VX，FX65 不修改 I，8XY1 不影响 VF
+    assert_quirks(&check("quirks", "modern"), [0, 8, 1, 7]);
+}
+
+#[test]
+fn quirks_vip() {
+    // COSMAC VIP：BNNN 使用 V0，以 VY 为源移位，FX65 增加 I，8XY1 清零 VF
+    assert_quirks(&check("quirks", "vip"), [0, 1, 2, 0]);
+}
+
+#[test]
+fn quirks_chip48() {
+    // CHIP-48：BXNN 使用 VX，只移位 VX，FX65 不修改 I，8XY1 不影响 VF
+    assert_quirks(&check("quirks", "chip48"), [1, 8, 1, 7]);
+}
+
+#[test]
+fn quirks_xochip() {
+    // XO-CHIP：BNNN 使用 V0，以 VY 为源移位，FX65 增加 I，8XY1 不影响 VF
+    assert_quirks(&check("quirks", "xochip"), [0, 1, 2, 7]);
+}
+
+#[test]
+fn schip_hires_and_scrolling() {
+    check("schip", "schip");
+}
+
+#[test]
+fn xochip_planes() {
+    check("planes", "xochip");
+}